@@ -0,0 +1,26 @@
+//! Count how many times each of a function's arguments is read.
+
+use crate::function::Function;
+use crate::ir::Expr;
+use std::collections::HashMap;
+
+/// Compute the number of `get_local`s of each of the given function's
+/// arguments.
+///
+/// Every argument is present in the resulting map, so arguments that are never
+/// read have a count of zero. Reads that are no longer part of the function,
+/// such as those in blocks that cannot be reached, are not counted. See
+/// `Function::used_exprs`.
+pub fn compute(func: &Function) -> HashMap<u32, usize> {
+    let mut counts: HashMap<u32, usize> = (0..func.params.len() as u32).map(|a| (a, 0)).collect();
+
+    for id in func.used_exprs() {
+        if let Expr::GetLocal { local, .. } = func.exprs[id] {
+            if let Some(count) = counts.get_mut(&local) {
+                *count += 1;
+            }
+        }
+    }
+
+    counts
+}
@@ -0,0 +1,99 @@
+//! A rough model of how expensive a function is to execute.
+
+use crate::function::Function;
+use crate::ir::Expr;
+
+/// The weight given to each kind of expression when estimating a function's
+/// cost with `Function::estimated_cost`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CostModel {
+    /// Reading or writing a local.
+    pub local: u64,
+    /// Reading or writing a global.
+    pub global: u64,
+    /// Materializing a constant.
+    pub constant: u64,
+    /// Cheap arithmetic, comparisons, and `select`.
+    pub arithmetic: u64,
+    /// Integer multiplication.
+    pub multiplication: u64,
+    /// Integer division and remainder.
+    pub division: u64,
+    /// Loading from or storing to memory, and atomic memory accesses.
+    pub memory: u64,
+    /// Splatting, extracting, and replacing vector lanes, and lane-wise
+    /// vector arithmetic.
+    pub vector: u64,
+    /// Branches, returns, and other control flow.
+    pub control: u64,
+}
+
+impl Default for CostModel {
+    fn default() -> CostModel {
+        CostModel {
+            local: 0,
+            global: 1,
+            constant: 0,
+            arithmetic: 1,
+            multiplication: 3,
+            division: 20,
+            memory: 5,
+            vector: 2,
+            control: 2,
+        }
+    }
+}
+
+impl CostModel {
+    /// The weight of a single expression, not counting its operands.
+    pub fn weight(&self, expr: &Expr) -> u64 {
+        match expr {
+            Expr::Phi => 0,
+            Expr::GetLocal { .. } | Expr::SetLocal { .. } => self.local,
+            Expr::GetGlobal { .. } | Expr::SetGlobal { .. } => self.global,
+            Expr::I32Const(_) => self.constant,
+            Expr::I32Add(..)
+            | Expr::I32Sub(..)
+            | Expr::I32Eqz(_)
+            | Expr::I32Eq(..)
+            | Expr::I32And(..)
+            | Expr::I32Popcnt(_)
+            | Expr::Select { .. }
+            | Expr::Drop(_) => self.arithmetic,
+            Expr::I32Mul(..) => self.multiplication,
+            Expr::I32DivS(..) | Expr::I32DivU(..) | Expr::I32RemS(..) | Expr::I32RemU(..) => {
+                self.division
+            }
+            Expr::Load { .. }
+            | Expr::Store { .. }
+            | Expr::AtomicRmw { .. }
+            | Expr::Cmpxchg { .. }
+            | Expr::AtomicWait { .. }
+            | Expr::AtomicNotify { .. } => self.memory,
+            Expr::Splat { .. }
+            | Expr::ExtractLane { .. }
+            | Expr::ReplaceLane { .. }
+            | Expr::VecBinop { .. }
+            | Expr::VecUnop { .. }
+            | Expr::Shuffle { .. } => self.vector,
+            Expr::Unreachable
+            | Expr::Br { .. }
+            | Expr::BrIf { .. }
+            | Expr::IfElse { .. }
+            | Expr::BrTable { .. }
+            | Expr::Return { .. } => self.control,
+        }
+    }
+
+    /// Estimate the cost of the given function: the sum of the weights of
+    /// every expression still used by it, each counted once even when it is
+    /// shared between several parents. Dead code that passes leave behind,
+    /// such as blocks that can no longer be reached, costs nothing. See
+    /// `Function::used_exprs`.
+    pub fn estimate(&self, func: &Function) -> u64 {
+        func.used_exprs()
+            .into_iter()
+            .map(|id| self.weight(&func.exprs[id]))
+            .sum()
+    }
+}
@@ -0,0 +1,84 @@
+//! Find every memory access in a function.
+
+use crate::function::Function;
+use crate::ir::{AtomicWidth, Expr, ExprId, LoadKind, MemArg, StoreKind};
+
+/// How a memory access touches memory.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Access {
+    /// A read by a load of the given kind.
+    Load(LoadKind),
+    /// A write by a store of the given kind.
+    Store(StoreKind),
+    /// A read and a write by an atomic read-modify-write or compare-exchange
+    /// of the given width.
+    Rmw(AtomicWidth),
+    /// A read by an `i32.atomic.wait`, or an `i64.atomic.wait` if
+    /// `sixty_four` is set.
+    Wait {
+        /// Is the waited-on value an `i64`?
+        sixty_four: bool,
+    },
+    /// An `atomic.notify`, which neither reads nor writes the `i32` at its
+    /// address.
+    Notify,
+}
+
+/// A single load from, store to, or atomic access of memory.
+///
+/// Only memory 0 can be accessed, so every access is to that memory.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MemoryAccess {
+    /// The expression making the access.
+    pub expr: ExprId,
+    /// Whether the access reads or writes, and with which instruction.
+    pub access: Access,
+    /// The alignment and static offset of the access.
+    pub arg: MemArg,
+}
+
+impl MemoryAccess {
+    /// Does this access write to memory?
+    pub fn is_write(&self) -> bool {
+        match self.access {
+            Access::Load(_) | Access::Wait { .. } | Access::Notify => false,
+            Access::Store(_) | Access::Rmw(_) => true,
+        }
+    }
+
+    /// The number of bytes accessed.
+    pub fn width(&self) -> u32 {
+        match self.access {
+            Access::Load(kind) => kind.width(),
+            Access::Store(kind) => kind.width(),
+            Access::Rmw(width) => width.width(),
+            Access::Wait { sixty_four: true } => 8,
+            Access::Wait { sixty_four: false } | Access::Notify => 4,
+        }
+    }
+}
+
+/// Compute every memory access that is still part of the given function.
+///
+/// Accesses are listed block by block, and in evaluation order within each
+/// block. See `Function::used_exprs`.
+pub fn compute(func: &Function) -> Vec<MemoryAccess> {
+    func.used_exprs()
+        .into_iter()
+        .filter_map(|expr| {
+            let (access, arg) = match func.exprs[expr] {
+                Expr::Load { kind, arg, .. } => (Access::Load(kind), arg),
+                Expr::Store { kind, arg, .. } => (Access::Store(kind), arg),
+                Expr::AtomicRmw { width, arg, .. } | Expr::Cmpxchg { width, arg, .. } => {
+                    (Access::Rmw(width), arg)
+                }
+                Expr::AtomicWait {
+                    sixty_four, arg, ..
+                } => (Access::Wait { sixty_four }, arg),
+                Expr::AtomicNotify { arg, .. } => (Access::Notify, arg),
+                _ => return None,
+            };
+            Some(MemoryAccess { expr, access, arg })
+        })
+        .collect()
+}
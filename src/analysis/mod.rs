@@ -0,0 +1,6 @@
+//! Analyses over a function's IR.
+
+pub mod arg_usage;
+pub mod cost;
+pub mod memory_accesses;
+pub mod operand_stack;
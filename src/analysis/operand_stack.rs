@@ -0,0 +1,55 @@
+//! Query the types of the operands on the stack at a given expression.
+
+use crate::function::Function;
+use crate::ir::ExprId;
+use crate::ValType;
+
+/// Get the types of the operands on the stack just before the instruction
+/// that created the given expression executes, from the bottom of the stack
+/// to the top. The expression's own operands are on top.
+///
+/// This is recorded while validating the function, so it is only available
+/// for expressions created from instructions of the original function body,
+/// and returns `None` for any other expression. For expressions that a
+/// `block`, `loop`, or `if` creates itself, such as the branches between its
+/// blocks, this is the stack before the whole construct. Operands of unknown
+/// type, which only occur in unreachable code, are `None`.
+pub fn at(func: &Function, expr: ExprId) -> Option<Vec<Option<ValType>>> {
+    let provenance = func.provenance(expr)?;
+    Some(func.stacks.stack(provenance.stack))
+}
+
+/// An operand recorded in a `StackHistory`.
+pub(crate) type StackEntry = usize;
+
+/// A persistent record of every operand stack seen while validating a
+/// function.
+///
+/// Each pushed operand gets an entry holding its type and the entry of the
+/// operand below it, so a whole stack is identified by the entry of its top
+/// operand, and remembering one does not need a copy of it.
+#[derive(Debug, Default)]
+pub(crate) struct StackHistory {
+    entries: Vec<(Option<ValType>, Option<StackEntry>)>,
+}
+
+impl StackHistory {
+    /// Record an operand of the given type pushed on top of `below`.
+    pub(crate) fn push(&mut self, ty: Option<ValType>, below: Option<StackEntry>) -> StackEntry {
+        self.entries.push((ty, below));
+        self.entries.len() - 1
+    }
+
+    /// Get the types of the stack whose top operand is `top`, from the bottom
+    /// of the stack to the top.
+    pub(crate) fn stack(&self, mut top: Option<StackEntry>) -> Vec<Option<ValType>> {
+        let mut stack = vec![];
+        while let Some(entry) = top {
+            let (ty, below) = self.entries[entry];
+            stack.push(ty);
+            top = below;
+        }
+        stack.reverse();
+        stack
+    }
+}
@@ -8,6 +8,22 @@ pub trait Dot {
     fn dot(&self, out: &mut Write) -> io::Result<()>;
 }
 
+/// Escape text for use inside an HTML-like dot label, `label=<...>`, which
+/// is how every label made of more than a fixed string is emitted.
+pub(crate) fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
 /// Append a port to the given dot label.
 #[derive(Debug)]
 pub struct Port<'a, D: 'a + Dot>(pub &'a D, pub &'a str);
@@ -34,4 +34,17 @@ pub enum ErrorKind {
     /// TODO
     #[fail(display = "The WebAssembly is invalid")]
     InvalidWasm,
+
+    /// The WebAssembly uses an instruction or feature that is not supported
+    /// yet.
+    #[fail(display = "The WebAssembly uses something that is not supported yet")]
+    Unsupported,
+
+    /// An id that does not belong to the function it was used with.
+    #[fail(display = "The id does not refer to anything in this function")]
+    UnknownId,
+
+    /// Text that does not parse as a value of the type it was parsed as.
+    #[fail(display = "The text is not a valid value of the given type")]
+    InvalidValue,
 }
@@ -0,0 +1,384 @@
+//! Building new functions from scratch.
+
+use super::context::{ControlStack, FunctionContext, OperandStack};
+use super::{
+    begin_block, begin_else, begin_function, begin_if, begin_loop, end_block, end_function, end_if,
+    end_loop, validate_single_instruction, Function, IfFrame,
+};
+use crate::error::{ErrorKind, Result};
+use crate::ir::BlockId;
+use crate::validation_context::ValidationContext;
+use crate::ValType;
+use failure::Fail;
+use parity_wasm::elements::{self, BlockType, Instruction};
+use std::slice;
+
+/// Build a new `Function` by appending instructions to its body, one at a
+/// time.
+///
+/// Each instruction is type checked with the same validation as parsed
+/// functions as soon as it is appended, so an ill-typed instruction is
+/// reported by the call that appends it, and is not added to the body.
+/// `finish` only has to check that the body leaves the function's results
+/// behind.
+///
+/// ```
+/// # fn main() -> Result<(), failure::Error> {
+/// use walrus::function::FunctionBuilder;
+/// use walrus::ValType;
+///
+/// let mut builder = FunctionBuilder::new(&[ValType::I32], Some(ValType::I32));
+/// builder.get_local(0)?.i32_const(1)?.i32_add()?;
+/// let func = builder.finish()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct FunctionBuilder {
+    params: Vec<ValType>,
+    result: Option<ValType>,
+    validation: ValidationContext<'static>,
+    func: Function,
+    func_exit: BlockId,
+    operands: OperandStack,
+    controls: ControlStack,
+    frames: Vec<Frame>,
+}
+
+/// A `block`, `loop`, or `if` that is open at the end of the body built so
+/// far, and what is needed to close it.
+#[derive(Clone, Copy, Debug)]
+enum Frame {
+    Block {
+        ty: BlockType,
+        continuation: BlockId,
+    },
+    Loop {
+        block: BlockId,
+    },
+    If {
+        ty: BlockType,
+        frame: IfFrame,
+        has_else: bool,
+    },
+}
+
+impl Frame {
+    /// The type of this frame's label, which branches to it use.
+    fn label(&self) -> BlockType {
+        match *self {
+            Frame::Block { ty, .. } | Frame::If { ty, .. } => ty,
+            Frame::Loop { .. } => BlockType::NoResult,
+        }
+    }
+}
+
+impl FunctionBuilder {
+    /// Start building a function with the given parameters and result.
+    pub fn new(params: &[ValType], result: Option<ValType>) -> FunctionBuilder {
+        let validation = module_validation(params, result, None);
+        let mut func = Function::with_params(params.to_vec().into_boxed_slice());
+        let mut operands = OperandStack::new();
+        let mut controls = ControlStack::new();
+        let func_exit = {
+            let mut ctx =
+                FunctionContext::new(&mut func, &validation, &mut operands, &mut controls, &[]);
+            begin_function(&mut ctx, &result.into_iter().collect::<Vec<_>>())
+        };
+        FunctionBuilder {
+            params: params.to_vec(),
+            result,
+            validation,
+            func,
+            func_exit,
+            operands,
+            controls,
+            frames: vec![],
+        }
+    }
+
+    /// Give the function a linear memory with the given limits, in pages, to
+    /// load from and store to. Without one, every memory instruction is
+    /// rejected.
+    pub fn with_memory(mut self, initial: u32, maximum: Option<u32>) -> FunctionBuilder {
+        let memory = elements::MemoryType::new(initial, maximum);
+        self.validation = module_validation(&self.params, self.result, Some(memory));
+        self
+    }
+
+    /// Declare a new local of the given type, returning its index.
+    pub fn add_local(&mut self, ty: ValType) -> u32 {
+        self.func.add_local(ty)
+    }
+
+    /// Append an arbitrary instruction, if it is valid after the body built
+    /// so far.
+    ///
+    /// Only the new instruction is validated, against the operand and control
+    /// stacks left by the instructions before it.
+    pub fn instr(&mut self, instr: Instruction) -> Result<&mut FunctionBuilder> {
+        // Validation can fail after popping some operands, so put the stacks
+        // back the way they were if it does.
+        let operands = self.operands.clone();
+        let controls = self.controls.clone();
+        match self.validate(&instr) {
+            Ok(()) => Ok(self),
+            Err(e) => {
+                self.operands = operands;
+                self.controls = controls;
+                Err(e)
+            }
+        }
+    }
+
+    /// Append `get_local n`.
+    pub fn get_local(&mut self, n: u32) -> Result<&mut FunctionBuilder> {
+        self.instr(Instruction::GetLocal(n))
+    }
+
+    /// Append `set_local n`.
+    pub fn set_local(&mut self, n: u32) -> Result<&mut FunctionBuilder> {
+        self.instr(Instruction::SetLocal(n))
+    }
+
+    /// Append `i32.const n`.
+    pub fn i32_const(&mut self, n: i32) -> Result<&mut FunctionBuilder> {
+        self.instr(Instruction::I32Const(n))
+    }
+
+    /// Append `i32.add`.
+    pub fn i32_add(&mut self) -> Result<&mut FunctionBuilder> {
+        self.instr(Instruction::I32Add)
+    }
+
+    /// Append `i32.sub`.
+    pub fn i32_sub(&mut self) -> Result<&mut FunctionBuilder> {
+        self.instr(Instruction::I32Sub)
+    }
+
+    /// Append `i32.mul`.
+    pub fn i32_mul(&mut self) -> Result<&mut FunctionBuilder> {
+        self.instr(Instruction::I32Mul)
+    }
+
+    /// Append `i32.eqz`.
+    pub fn i32_eqz(&mut self) -> Result<&mut FunctionBuilder> {
+        self.instr(Instruction::I32Eqz)
+    }
+
+    /// Append `i32.eq`.
+    pub fn i32_eq(&mut self) -> Result<&mut FunctionBuilder> {
+        self.instr(Instruction::I32Eq)
+    }
+
+    /// Append `i32.and`.
+    pub fn i32_and(&mut self) -> Result<&mut FunctionBuilder> {
+        self.instr(Instruction::I32And)
+    }
+
+    /// Append `drop`.
+    pub fn drop(&mut self) -> Result<&mut FunctionBuilder> {
+        self.instr(Instruction::Drop)
+    }
+
+    /// Append `select`.
+    pub fn select(&mut self) -> Result<&mut FunctionBuilder> {
+        self.instr(Instruction::Select)
+    }
+
+    /// Append `unreachable`.
+    pub fn unreachable(&mut self) -> Result<&mut FunctionBuilder> {
+        self.instr(Instruction::Unreachable)
+    }
+
+    /// Append `return`.
+    pub fn return_(&mut self) -> Result<&mut FunctionBuilder> {
+        self.instr(Instruction::Return)
+    }
+
+    /// Open a `block` with the given result type. Close it with `end`.
+    pub fn block(&mut self, result: Option<ValType>) -> Result<&mut FunctionBuilder> {
+        self.instr(Instruction::Block(block_type(result)))
+    }
+
+    /// Open a `loop` with the given result type. Close it with `end`.
+    pub fn loop_(&mut self, result: Option<ValType>) -> Result<&mut FunctionBuilder> {
+        self.instr(Instruction::Loop(block_type(result)))
+    }
+
+    /// Open an `if` with the given result type. Close it with `end`, with an
+    /// optional `else_` in between.
+    pub fn if_(&mut self, result: Option<ValType>) -> Result<&mut FunctionBuilder> {
+        self.instr(Instruction::If(block_type(result)))
+    }
+
+    /// Append `else`.
+    pub fn else_(&mut self) -> Result<&mut FunctionBuilder> {
+        self.instr(Instruction::Else)
+    }
+
+    /// Append `end`, closing the innermost open `block`, `loop`, or `if`.
+    pub fn end(&mut self) -> Result<&mut FunctionBuilder> {
+        self.instr(Instruction::End)
+    }
+
+    /// Append `br n`.
+    pub fn br(&mut self, n: u32) -> Result<&mut FunctionBuilder> {
+        self.instr(Instruction::Br(n))
+    }
+
+    /// Append `br_if n`.
+    pub fn br_if(&mut self, n: u32) -> Result<&mut FunctionBuilder> {
+        self.instr(Instruction::BrIf(n))
+    }
+
+    /// Check that the body leaves the function's results behind, and
+    /// construct its `Function`.
+    pub fn finish(mut self) -> Result<Function> {
+        if !self.frames.is_empty() {
+            return Err(ErrorKind::InvalidWasm.context("expected `end`").into());
+        }
+        let validation = function_validation(&self.validation, &self.func.locals)?;
+        let mut ctx = FunctionContext::new(
+            &mut self.func,
+            &validation,
+            &mut self.operands,
+            &mut self.controls,
+            &[],
+        );
+        end_function(&mut ctx, self.func_exit)?;
+        Ok(self.func)
+    }
+
+    /// Validate the given instruction after the body built so far, and add
+    /// it to the function.
+    fn validate(&mut self, instr: &Instruction) -> Result<()> {
+        let validation = function_validation(&self.validation, &self.func.locals)?;
+        let labels: Vec<_> = self.frames.iter().map(Frame::label).collect();
+        let validation = validation.for_labels(&labels);
+        let mut ctx = FunctionContext::new(
+            &mut self.func,
+            &validation,
+            &mut self.operands,
+            &mut self.controls,
+            &[],
+        );
+
+        match instr {
+            Instruction::Block(ty) => {
+                let continuation = begin_block(&mut ctx, ty);
+                self.frames.push(Frame::Block {
+                    ty: *ty,
+                    continuation,
+                });
+            }
+            Instruction::Loop(ty) => {
+                let block = begin_loop(&mut ctx, ty);
+                self.frames.push(Frame::Loop { block });
+            }
+            Instruction::If(ty) => {
+                let frame = begin_if(&mut ctx, ty)?;
+                self.frames.push(Frame::If {
+                    ty: *ty,
+                    frame,
+                    has_else: false,
+                });
+            }
+            Instruction::Else => match self.frames.last_mut() {
+                Some(Frame::If {
+                    frame,
+                    has_else: has_else @ false,
+                    ..
+                }) => {
+                    begin_else(&mut ctx, frame)?;
+                    *has_else = true;
+                }
+                _ => {
+                    return Err(ErrorKind::InvalidWasm
+                        .context("`else` without a leading `if`")
+                        .into());
+                }
+            },
+            Instruction::End => {
+                match self.frames.last() {
+                    Some(Frame::Block { continuation, .. }) => end_block(&mut ctx, *continuation)?,
+                    Some(Frame::Loop { block }) => end_loop(&mut ctx, *block)?,
+                    Some(Frame::If {
+                        ty,
+                        frame,
+                        has_else,
+                    }) => {
+                        if !has_else {
+                            // Check this up front, because closing the
+                            // consequent adds the `if` to its block.
+                            if *ty != BlockType::NoResult {
+                                return Err(ErrorKind::InvalidWasm
+                                    .context("`if` without an `else` cannot produce results")
+                                    .into());
+                            }
+                            begin_else(&mut ctx, frame)?;
+                        }
+                        end_if(&mut ctx, frame, *has_else)?;
+                    }
+                    None => {
+                        return Err(ErrorKind::InvalidWasm
+                            .context("`end` without an open `block`, `loop`, or `if`")
+                            .into());
+                    }
+                }
+                self.frames.pop();
+            }
+            _ => {
+                validate_single_instruction(&mut ctx, slice::from_ref(instr))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The validation context for the function's body with the given locals,
+/// outside of any `block`, `loop`, or `if`.
+fn function_validation<'a>(
+    module: &'a ValidationContext<'static>,
+    locals: &[ValType],
+) -> Result<ValidationContext<'a>> {
+    let locals = locals
+        .iter()
+        .map(|ty| elements::Local::new(1, (*ty).into()))
+        .collect();
+    let body = elements::FuncBody::new(locals, elements::Instructions::empty());
+    module.for_function(&elements::Func::new(0), &body)
+}
+
+/// A validation context for a module with nothing but the function being
+/// built, and the given memory.
+fn module_validation(
+    params: &[ValType],
+    result: Option<ValType>,
+    memory: Option<elements::MemoryType>,
+) -> ValidationContext<'static> {
+    let ty = elements::FunctionType::new(
+        params.iter().cloned().map(Into::into).collect(),
+        result.map(Into::into),
+    );
+    let types = elements::TypeSection::with_types(vec![elements::Type::Function(ty)]);
+    let func = elements::Func::new(0);
+    let mut sections = vec![
+        elements::Section::Type(types),
+        elements::Section::Function(elements::FunctionSection::with_entries(vec![func])),
+    ];
+    if let Some(memory) = memory {
+        sections.push(elements::Section::Memory(
+            elements::MemorySection::with_entries(vec![memory]),
+        ));
+    }
+    let module = elements::Module::new(sections);
+    ValidationContext::for_module(&module).expect("a module with one function type is valid")
+}
+
+fn block_type(result: Option<ValType>) -> BlockType {
+    match result {
+        Some(ty) => BlockType::Value(ty.into()),
+        None => BlockType::NoResult,
+    }
+}
@@ -4,10 +4,11 @@ use super::super::error::{ErrorKind, Result};
 use super::super::validation_context::ValidationContext;
 use super::Function;
 use super::ValType;
+use crate::analysis::operand_stack::{StackEntry, StackHistory};
 use crate::ir::{Block, BlockId, ExprId};
-use failure::Fail;
+use failure::{Fail, ResultExt};
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct ControlFrame {
     /// The type of the associated label (used to type-check branches).
     pub label_types: Vec<ValType>,
@@ -24,8 +25,14 @@ pub struct ControlFrame {
     /// it unreachable is given.
     pub unreachable: Option<ExprId>,
 
-    /// The id of this control frame's block.
+    /// The id of the block that code in this control frame is currently
+    /// added to. Nested control instructions move this on to their
+    /// continuation blocks.
     pub block: BlockId,
+
+    /// The id of the block that this control frame opened with, which is
+    /// the one to name in errors about the frame.
+    pub opened: BlockId,
 }
 
 /// The operand stack.
@@ -33,8 +40,8 @@ pub struct ControlFrame {
 /// `None` is used for `Unknown` stack-polymophic values.
 ///
 /// We also keep track of the expression that created the value at each stack
-/// slot.
-pub type OperandStack = Vec<(Option<ValType>, ExprId)>;
+/// slot, and the slot's entry in the function's `StackHistory`.
+pub type OperandStack = Vec<(Option<ValType>, ExprId, StackEntry)>;
 
 /// The control frame stack.
 pub type ControlStack = Vec<ControlFrame>;
@@ -52,6 +59,10 @@ pub struct FunctionContext<'a> {
 
     /// The control frames stack.
     pub controls: &'a mut ControlStack,
+
+    /// The offset of each of the function body's instructions in the code
+    /// section, or nothing if the body's original encoding is not known.
+    pub offsets: &'a [usize],
 }
 
 impl<'a> FunctionContext<'a> {
@@ -61,12 +72,14 @@ impl<'a> FunctionContext<'a> {
         validation: &'a ValidationContext<'a>,
         operands: &'a mut OperandStack,
         controls: &'a mut ControlStack,
+        offsets: &'a [usize],
     ) -> FunctionContext<'a> {
         FunctionContext {
             func,
             validation,
             operands,
             controls,
+            offsets,
         }
     }
 
@@ -76,11 +89,12 @@ impl<'a> FunctionContext<'a> {
             validation,
             operands: self.operands,
             controls: self.controls,
+            offsets: self.offsets,
         }
     }
 
     pub fn push_operand(&mut self, op: Option<ValType>, expr: ExprId) {
-        impl_push_operand(&mut self.operands, op, expr);
+        impl_push_operand(self.operands, &mut self.func.stacks, op, expr);
     }
 
     pub fn pop_operand(&mut self) -> Result<(Option<ValType>, ExprId)> {
@@ -95,7 +109,7 @@ impl<'a> FunctionContext<'a> {
     }
 
     pub fn push_operands(&mut self, types: &[ValType], exprs: &[ExprId]) {
-        impl_push_operands(&mut self.operands, types, exprs)
+        impl_push_operands(self.operands, &mut self.func.stacks, types, exprs)
     }
 
     pub fn pop_operands(&mut self, expected: &[ValType]) -> Result<Vec<ExprId>> {
@@ -151,8 +165,15 @@ impl<'a> FunctionContext<'a> {
     }
 }
 
-fn impl_push_operand(operands: &mut OperandStack, op: Option<ValType>, expr: ExprId) {
-    operands.push((op, expr));
+fn impl_push_operand(
+    operands: &mut OperandStack,
+    stacks: &mut StackHistory,
+    op: Option<ValType>,
+    expr: ExprId,
+) {
+    let below = operands.last().map(|(_, _, entry)| *entry);
+    let entry = stacks.push(op, below);
+    operands.push((op, expr, entry));
 }
 
 fn impl_pop_operand(
@@ -171,7 +192,8 @@ fn impl_pop_operand(
                 .into());
         }
     }
-    Ok(operands.pop().unwrap())
+    let (op, expr, _) = operands.pop().unwrap();
+    Ok((op, expr))
 }
 
 fn impl_pop_operand_expected(
@@ -195,9 +217,14 @@ fn impl_pop_operand_expected(
     }
 }
 
-fn impl_push_operands(operands: &mut OperandStack, types: &[ValType], exprs: &[ExprId]) {
+fn impl_push_operands(
+    operands: &mut OperandStack,
+    stacks: &mut StackHistory,
+    types: &[ValType],
+    exprs: &[ExprId],
+) {
     for (ty, expr) in types.iter().zip(exprs.iter()) {
-        impl_push_operand(operands, Some(*ty), *expr);
+        impl_push_operand(operands, stacks, Some(*ty), *expr);
     }
 }
 
@@ -231,6 +258,7 @@ fn impl_push_control(
         height: operands.len(),
         unreachable: None,
         block,
+        opened: block,
     };
     controls.push(frame);
     block
@@ -243,14 +271,15 @@ fn impl_pop_control(
     let frame = controls.last().ok_or_else(|| {
         ErrorKind::InvalidWasm.context("attempted to pop a frame from an empty control stack")
     })?;
-    let exprs = impl_pop_operands(operands, controls, &frame.end_types)?;
+    let exprs = impl_pop_operands(operands, controls, &frame.end_types)
+        .with_context(|_| format!("at the end of block_{}", frame.opened.index()))?;
     if operands.len() != frame.height {
         return Err(ErrorKind::InvalidWasm
             .context(format!(
-                "incorrect number of operands on the stack at the end of a control frame; \
-                 found {}, expected {}",
-                operands.len(),
-                frame.height
+                "block_{} leaves {} extra operand(s) on the stack at its end, beyond its \
+                 result types",
+                frame.opened.index(),
+                operands.len() - frame.height
             ))
             .into());
     }
@@ -1,6 +1,6 @@
 //! Displaying IR.
 
-use super::super::ir::{Block, Expr, ExprId};
+use super::super::ir::{access_name, extract_lane_suffix, write_mem_arg, Block, Expr, ExprId};
 use super::Function;
 use petgraph::visit;
 use std::fmt;
@@ -28,7 +28,14 @@ impl DisplayIr for Function {
             blocks.push(b);
         }
 
-        writeln!(f, "func {{")?;
+        match &self.name {
+            Some(name) => {
+                write!(f, "func ")?;
+                write_id(f, name)?;
+                writeln!(f, " {{")?;
+            }
+            None => writeln!(f, "func {{")?,
+        }
         for (i, id) in blocks.iter().rev().enumerate() {
             let block = &self.blocks[*id];
             if i != 0 {
@@ -145,6 +152,9 @@ impl DisplayIr for Expr {
             Expr::GetLocal { ty: _, local } => {
                 write!(f, "(get_local {})", local)?;
             }
+            Expr::GetGlobal { ty: _, global } => {
+                write!(f, "(get_global {})", global)?;
+            }
             Expr::I32Add(lhs, rhs) => {
                 binop(f, func, "i32.add", *lhs, *rhs)?;
             }
@@ -154,9 +164,27 @@ impl DisplayIr for Expr {
             Expr::I32Eqz(e) => {
                 unop(f, func, "i32.eqz", *e)?;
             }
+            Expr::I32Eq(lhs, rhs) => {
+                binop(f, func, "i32.eq", *lhs, *rhs)?;
+            }
+            Expr::I32And(lhs, rhs) => {
+                binop(f, func, "i32.and", *lhs, *rhs)?;
+            }
             Expr::I32Mul(lhs, rhs) => {
                 binop(f, func, "i32.mul", *lhs, *rhs)?;
             }
+            Expr::I32DivS(lhs, rhs) => {
+                binop(f, func, "i32.div_s", *lhs, *rhs)?;
+            }
+            Expr::I32DivU(lhs, rhs) => {
+                binop(f, func, "i32.div_u", *lhs, *rhs)?;
+            }
+            Expr::I32RemS(lhs, rhs) => {
+                binop(f, func, "i32.rem_s", *lhs, *rhs)?;
+            }
+            Expr::I32RemU(lhs, rhs) => {
+                binop(f, func, "i32.rem_u", *lhs, *rhs)?;
+            }
             Expr::I32Popcnt(e) => {
                 unop(f, func, "i32.popcnt", *e)?;
             }
@@ -212,6 +240,153 @@ impl DisplayIr for Expr {
                 func.exprs.get(*value).unwrap().display_ir(f, func)?;
                 write!(f, ")")?;
             }
+            Expr::SetGlobal {
+                ty: _,
+                value,
+                global,
+            } => {
+                write!(f, "(set_global {} ", global)?;
+                func.exprs.get(*value).unwrap().display_ir(f, func)?;
+                write!(f, ")")?;
+            }
+            Expr::Load {
+                kind,
+                atomic,
+                arg,
+                address,
+            } => {
+                write!(f, "({}", access_name(kind, *atomic))?;
+                write_mem_arg(f, arg, kind.width())?;
+                write!(f, " ")?;
+                func.exprs.get(*address).unwrap().display_ir(f, func)?;
+                write!(f, ")")?;
+            }
+            Expr::Store {
+                kind,
+                atomic,
+                arg,
+                address,
+                value,
+            } => {
+                write!(f, "({}", access_name(kind, *atomic))?;
+                write_mem_arg(f, arg, kind.width())?;
+                write!(f, " ")?;
+                func.exprs.get(*address).unwrap().display_ir(f, func)?;
+                write!(f, " ")?;
+                func.exprs.get(*value).unwrap().display_ir(f, func)?;
+                write!(f, ")")?;
+            }
+            Expr::AtomicRmw {
+                op,
+                width,
+                arg,
+                address,
+                value,
+            } => {
+                write!(f, "({}.{}", width, op)?;
+                write_mem_arg(f, arg, width.width())?;
+                write!(f, " ")?;
+                func.exprs.get(*address).unwrap().display_ir(f, func)?;
+                write!(f, " ")?;
+                func.exprs.get(*value).unwrap().display_ir(f, func)?;
+                write!(f, ")")?;
+            }
+            Expr::Cmpxchg {
+                width,
+                arg,
+                address,
+                expected,
+                replacement,
+            } => {
+                write!(f, "({}.cmpxchg", width)?;
+                write_mem_arg(f, arg, width.width())?;
+                for e in &[address, expected, replacement] {
+                    write!(f, " ")?;
+                    func.exprs.get(**e).unwrap().display_ir(f, func)?;
+                }
+                write!(f, ")")?;
+            }
+            Expr::AtomicWait {
+                sixty_four,
+                arg,
+                address,
+                expected,
+                timeout,
+            } => {
+                let (ty, width) = if *sixty_four { ("i64", 8) } else { ("i32", 4) };
+                write!(f, "({}.atomic.wait", ty)?;
+                write_mem_arg(f, arg, width)?;
+                for e in &[address, expected, timeout] {
+                    write!(f, " ")?;
+                    func.exprs.get(**e).unwrap().display_ir(f, func)?;
+                }
+                write!(f, ")")?;
+            }
+            Expr::AtomicNotify {
+                arg,
+                address,
+                count,
+            } => {
+                write!(f, "(atomic.notify")?;
+                write_mem_arg(f, arg, 4)?;
+                for e in &[address, count] {
+                    write!(f, " ")?;
+                    func.exprs.get(**e).unwrap().display_ir(f, func)?;
+                }
+                write!(f, ")")?;
+            }
+            Expr::Splat { shape, value } => {
+                unop(f, func, &format!("{}.splat", shape), *value)?;
+            }
+            Expr::ExtractLane {
+                shape,
+                signed,
+                lane,
+                vector,
+            } => {
+                write!(
+                    f,
+                    "({}.extract_lane{} {} ",
+                    shape,
+                    extract_lane_suffix(*shape, *signed),
+                    lane
+                )?;
+                func.exprs.get(*vector).unwrap().display_ir(f, func)?;
+                write!(f, ")")?;
+            }
+            Expr::ReplaceLane {
+                shape,
+                lane,
+                vector,
+                value,
+            } => {
+                write!(f, "({}.replace_lane {} ", shape, lane)?;
+                func.exprs.get(*vector).unwrap().display_ir(f, func)?;
+                write!(f, " ")?;
+                func.exprs.get(*value).unwrap().display_ir(f, func)?;
+                write!(f, ")")?;
+            }
+            Expr::VecBinop {
+                shape,
+                op,
+                lhs,
+                rhs,
+            } => {
+                binop(f, func, &format!("{}.{}", shape, op), *lhs, *rhs)?;
+            }
+            Expr::VecUnop { shape, op, value } => {
+                unop(f, func, &format!("{}.{}", shape, op), *value)?;
+            }
+            Expr::Shuffle { lanes, lhs, rhs } => {
+                let lanes: Vec<String> = lanes.iter().map(|l| l.to_string()).collect();
+                binop(
+                    f,
+                    func,
+                    &format!("v8x16.shuffle {}", lanes.join(" ")),
+                    *lhs,
+                    *rhs,
+                )?;
+            }
             Expr::Unreachable => {
                 write!(f, "(unreachable)")?;
             }
@@ -219,3 +394,22 @@ impl DisplayIr for Expr {
         Ok(())
     }
 }
+
+/// Write a `$name` identifier in the text format. Names with characters that
+/// identifiers cannot contain are written as quoted strings, `$"name"`.
+fn write_id(f: &mut fmt::Formatter, name: &str) -> fmt::Result {
+    let is_idchar = |c: char| c.is_ascii_alphanumeric() || "!#$%&'*+-./:<=>?@\\^_`|~".contains(c);
+    if !name.is_empty() && name.chars().all(is_idchar) {
+        return write!(f, "${}", name);
+    }
+    write!(f, "$\"")?;
+    for c in name.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            c if c.is_control() => write!(f, "\\u{{{:x}}}", c as u32)?,
+            _ => write!(f, "{}", c)?,
+        }
+    }
+    write!(f, "\"")
+}
@@ -1,18 +1,27 @@
 //! TODO
 
+mod builder;
 mod context;
 pub mod display;
 mod graph;
 
+pub use self::builder::FunctionBuilder;
 use self::context::FunctionContext;
-use super::dot::Dot;
+use super::dot::{escape_html, Dot};
 use super::error::{ErrorKind, Result};
 use super::validation_context::ValidationContext;
 use super::ValType;
-use crate::ir::{Block, BlockId, Expr, ExprId};
+use crate::analysis::cost::CostModel;
+use crate::analysis::operand_stack::{StackEntry, StackHistory};
+use crate::ir::{
+    access_name, AtomicOp, AtomicWidth, Block, BlockId, Expr, ExprId, LoadKind, MemArg, StoreKind,
+    VecBinaryOp, VecShape, VecUnaryOp,
+};
 use failure::{Fail, ResultExt};
 use id_arena::Arena;
-use parity_wasm::elements::{self, Instruction};
+use parity_wasm::elements::{self, Deserialize, Instruction};
+use petgraph::visit;
+use std::collections::HashSet;
 use std::fmt;
 use std::io::{self, Write};
 
@@ -23,17 +32,153 @@ pub struct Function {
     pub(crate) blocks: Arena<Block>,
     exit_block: Option<BlockId>,
     entry_block: Option<BlockId>,
-    // TODO: provenance: ExprId -> offset in code section of the original
-    // instruction
+    /// An optional name for this function, for debugging.
+    name: Option<String>,
+    /// The types of this function's parameters, which are its first locals.
+    pub(crate) params: Box<[ValType]>,
+    /// The types of the locals declared in this function's body, which come
+    /// after its parameters.
+    pub(crate) locals: Vec<ValType>,
+    /// Every operand stack seen while validating this function.
+    pub(crate) stacks: StackHistory,
+    /// What was recorded about each expression created while parsing,
+    /// indexed by the expression's index in `exprs`.
+    pub(crate) provenance: Vec<Option<Provenance>>,
+}
+
+/// What is recorded about an expression created while parsing a function.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Provenance {
+    /// The offset of the instruction that created the expression in the code
+    /// section, if the function was parsed from its original encoding.
+    pub offset: Option<usize>,
+    /// The top of the operand stack just before that instruction executed.
+    pub stack: Option<StackEntry>,
+}
+
+/// A function body as it is encoded in a module's code section.
+#[derive(Clone, Copy, Debug)]
+pub struct RawBody<'a> {
+    /// The offset of the body's encoding from the start of the code
+    /// section's contents, which is just after the section's id and size.
+    pub offset: usize,
+    /// The body's encoding, without the size in front of it: its locals
+    /// followed by its code.
+    pub bytes: &'a [u8],
+}
+
+/// Find each function body in the code section of the given module's
+/// encoding, in order.
+pub fn raw_bodies(wasm: &[u8]) -> Result<Vec<RawBody<'_>>> {
+    if wasm.len() < 8 || &wasm[..4] != b"\0asm" {
+        return Err(ErrorKind::InvalidWasm.context("not a wasm module").into());
+    }
+
+    let mut sections = io::Cursor::new(&wasm[8..]);
+    while (sections.position() as usize) < sections.get_ref().len() {
+        let id = read_bytes(&mut sections, 1)?[0];
+        let size = read_var_u32(&mut sections)?;
+        let contents = read_bytes(&mut sections, size as usize)?;
+        if id != 10 {
+            continue;
+        }
+
+        let mut reader = io::Cursor::new(contents);
+        let count = read_var_u32(&mut reader)?;
+        let mut bodies = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let size = read_var_u32(&mut reader)?;
+            let offset = reader.position() as usize;
+            let bytes = read_bytes(&mut reader, size as usize)?;
+            bodies.push(RawBody { offset, bytes });
+        }
+        return Ok(bodies);
+    }
+    Ok(vec![])
+}
+
+fn read_var_u32(reader: &mut io::Cursor<&[u8]>) -> Result<u32> {
+    let n = elements::VarUint32::deserialize(reader).map_err(|e| {
+        ErrorKind::InvalidWasm.context(format!("could not decode a LEB128 integer: {}", e))
+    })?;
+    Ok(n.into())
+}
+
+fn read_bytes<'a>(reader: &mut io::Cursor<&'a [u8]>, len: usize) -> Result<&'a [u8]> {
+    let start = reader.position() as usize;
+    let bytes = *reader.get_ref();
+    let bytes = start
+        .checked_add(len)
+        .and_then(|end| bytes.get(start..end))
+        .ok_or_else(|| ErrorKind::InvalidWasm.context("unexpected end of the module"))?;
+    reader.set_position((start + len) as u64);
+    Ok(bytes)
 }
 
 impl Function {
-    /// TODO
+    /// Construct a function from a body that was parsed by parity-wasm.
+    ///
+    /// The body's original encoding is not known, so none of the function's
+    /// expressions have original offsets. See `from_raw`.
     pub fn new(
         validation: &ValidationContext,
         types: &elements::TypeSection,
         func: &elements::Func,
         body: &elements::FuncBody,
+    ) -> Result<Function> {
+        Function::parse(validation, types, func, body, &[])
+    }
+
+    /// Construct a function from its body's original encoding, recording
+    /// where in the code section each of its instructions is. See
+    /// `original_offset` and `raw_bodies`.
+    pub fn from_raw(
+        validation: &ValidationContext,
+        types: &elements::TypeSection,
+        func: &elements::Func,
+        raw: RawBody,
+    ) -> Result<Function> {
+        let invalid = |e: elements::Error| {
+            ErrorKind::InvalidWasm.context(format!("could not decode the function body: {}", e))
+        };
+        let mut reader = io::Cursor::new(raw.bytes);
+        let locals = elements::CountedList::<elements::Local>::deserialize(&mut reader)
+            .map_err(invalid)?
+            .into_inner();
+
+        // Decode the code one instruction at a time, the way parity-wasm
+        // does, to see where each one starts.
+        let mut insts = vec![];
+        let mut offsets = vec![];
+        let mut depth = 1;
+        while depth > 0 {
+            offsets.push(raw.offset + reader.position() as usize);
+            let inst = Instruction::deserialize(&mut reader).map_err(invalid)?;
+            if inst.is_terminal() {
+                depth -= 1;
+            } else if inst.is_block() {
+                depth += 1;
+            }
+            insts.push(inst);
+        }
+        if reader.position() as usize != raw.bytes.len() {
+            return Err(ErrorKind::InvalidWasm
+                .context("trailing bytes after the function body's final `end`")
+                .into());
+        }
+
+        let body = elements::FuncBody::new(locals, elements::Instructions::new(insts));
+        Function::parse(validation, types, func, &body, &offsets)
+    }
+
+    /// Validate the given body and construct its function, with the given
+    /// original offsets of its instructions, if they are known.
+    fn parse(
+        validation: &ValidationContext,
+        types: &elements::TypeSection,
+        func: &elements::Func,
+        body: &elements::FuncBody,
+        offsets: &[usize],
     ) -> Result<Function> {
         let validation = validation.for_function(func, body)?;
 
@@ -46,12 +191,11 @@ impl Function {
             elements::Type::Function(f) => f,
         };
 
-        let mut func = Function {
-            blocks: Arena::new(),
-            exprs: Arena::new(),
-            entry_block: None,
-            exit_block: None,
-        };
+        let mut func = Function::with_params(ty.params().iter().map(ValType::from).collect());
+        for local in body.locals() {
+            let ty = ValType::from(&local.value_type());
+            func.locals.extend((0..local.count()).map(|_| ty));
+        }
 
         let result: Vec<_> = ty
             .return_type()
@@ -63,21 +207,17 @@ impl Function {
         let operands = &mut context::OperandStack::new();
         let controls = &mut context::ControlStack::new();
 
-        let mut ctx = FunctionContext::new(&mut func, &validation, operands, controls);
-
-        let func_exit = ctx.func.blocks.alloc(Block::new(
-            "function exit",
-            result.clone().into_boxed_slice(),
-        ));
-        ctx.func.exit_block = Some(func_exit);
-
-        let func_entry = ctx.push_control("function entry", vec![], result.clone(), func_exit);
-        ctx.func.entry_block = Some(func_entry);
+        let mut ctx = FunctionContext::new(&mut func, &validation, operands, controls, offsets);
 
-        let values =
-            validate_expression(&mut ctx, body.code().elements(), func_exit)?.into_boxed_slice();
-
-        ctx.func.finish_block(func_exit, Expr::Return { values });
+        let func_exit = begin_function(&mut ctx, &result);
+        let rest =
+            validate_instruction_sequence(&mut ctx, body.code().elements(), Instruction::End)?;
+        end_function(&mut ctx, func_exit)?;
+        if !rest.is_empty() {
+            return Err(ErrorKind::InvalidWasm
+                .context("trailing instructions after final `end`")
+                .into());
+        }
 
         if cfg!(debug_assertions) {
             assert_eq!(ctx.operands.len(), result.len());
@@ -98,6 +238,21 @@ impl Function {
         Ok(func)
     }
 
+    /// Create a function with the given parameters and no blocks yet.
+    fn with_params(params: Box<[ValType]>) -> Function {
+        Function {
+            blocks: Arena::new(),
+            exprs: Arena::new(),
+            entry_block: None,
+            exit_block: None,
+            name: None,
+            params,
+            locals: vec![],
+            stacks: StackHistory::default(),
+            provenance: vec![],
+        }
+    }
+
     fn finish_block(&mut self, block: BlockId, expr: Expr) {
         assert!(expr.is_jump());
         let block = self.blocks.get_mut(block).unwrap();
@@ -127,6 +282,229 @@ impl Function {
     pub fn exit_block(&self) -> BlockId {
         self.exit_block.unwrap()
     }
+
+    /// Get this function's name, if it has one.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_ref().map(|n| &n[..])
+    }
+
+    /// Set this function's name, which is shown when displaying it.
+    pub fn set_name<S: Into<String>>(&mut self, name: Option<S>) {
+        self.name = name.map(Into::into);
+    }
+
+    /// Get the types of this function's parameters. Parameters are always
+    /// the first locals, so parameter `n` is local `n`.
+    pub fn params(&self) -> &[ValType] {
+        &self.params
+    }
+
+    /// Get the types of the locals declared in this function's body. They
+    /// come after the parameters, so the first of them is local
+    /// `params().len()`.
+    pub fn locals(&self) -> &[ValType] {
+        &self.locals
+    }
+
+    /// Declare a new local of the given type in this function's body,
+    /// returning its index.
+    pub fn add_local(&mut self, ty: ValType) -> u32 {
+        self.locals.push(ty);
+        (self.params.len() + self.locals.len() - 1) as u32
+    }
+
+    /// Is the given local one of this function's parameters, rather than a
+    /// local declared in its body?
+    pub fn is_param(&self, local: u32) -> bool {
+        (local as usize) < self.params.len()
+    }
+
+    /// Get the block with the given id.
+    ///
+    /// Panics if the id is not one of this function's blocks. See `try_block`
+    /// for a non-panicking version.
+    pub fn block(&self, id: BlockId) -> &Block {
+        &self.blocks[id]
+    }
+
+    /// Get the block with the given id, or an error if the id is not one of
+    /// this function's blocks.
+    pub fn try_block(&self, id: BlockId) -> Result<&Block> {
+        self.blocks.get(id).ok_or_else(|| {
+            ErrorKind::UnknownId
+                .context(format!(
+                    "block_{} is not a block in this function",
+                    id.index()
+                ))
+                .into()
+        })
+    }
+
+    /// Get the expression with the given id.
+    ///
+    /// Panics if the id is not one of this function's expressions. See
+    /// `try_expr` for a non-panicking version.
+    pub fn expr(&self, id: ExprId) -> &Expr {
+        &self.exprs[id]
+    }
+
+    /// Get the expression with the given id, or an error if the id is not one
+    /// of this function's expressions.
+    pub fn try_expr(&self, id: ExprId) -> Result<&Expr> {
+        self.exprs.get(id).ok_or_else(|| {
+            ErrorKind::UnknownId
+                .context(format!(
+                    "expr_{} is not an expression in this function",
+                    id.index()
+                ))
+                .into()
+        })
+    }
+
+    /// Get the offset of the instruction that the given expression was
+    /// created from, relative to the start of the code section's contents,
+    /// just after the section's id and size. This is the base that DWARF for
+    /// wasm uses.
+    ///
+    /// The offsets are where the instructions were read from while decoding
+    /// the function's original encoding with `from_raw`, so they are exact
+    /// whatever LEB128 encodings the module used.
+    ///
+    /// Returns `None` for functions constructed with `new`, whose original
+    /// encoding is not known, and for expressions that were not created while
+    /// parsing the function, such as those added by passes.
+    pub fn original_offset(&self, expr: ExprId) -> Option<usize> {
+        self.provenance(expr).and_then(|p| p.offset)
+    }
+
+    /// Get what was recorded about the given expression while parsing.
+    pub(crate) fn provenance(&self, expr: ExprId) -> Option<&Provenance> {
+        self.exprs.get(expr)?;
+        self.provenance.get(expr.index())?.as_ref()
+    }
+
+    /// Estimate how expensive this function is to execute, weighting each
+    /// expression according to the given cost model.
+    pub fn estimated_cost(&self, weights: &CostModel) -> u64 {
+        weights.estimate(self)
+    }
+
+    /// Get every expression that is still used by a block reachable from
+    /// this function's entry, each only once, with operands before the
+    /// expressions that use them.
+    ///
+    /// Passes can leave expressions and blocks that nothing uses any more,
+    /// such as code removed by dead code elimination, behind in the arenas,
+    /// so this is what analyses of the function should walk rather than
+    /// every expression.
+    pub fn used_exprs(&self) -> Vec<ExprId> {
+        // Visit blocks in reverse post order, like `Display` does.
+        let cfg = self.cfg();
+        let mut dfs = visit::DfsPostOrder::new(&cfg, self.entry_block());
+        let mut blocks = vec![];
+        while let Some(b) = dfs.next(&cfg) {
+            blocks.push(b);
+        }
+
+        let mut seen = HashSet::new();
+        let mut used = vec![];
+        for block in blocks.into_iter().rev() {
+            for root in self.blocks[block].exprs.iter() {
+                // Each expression is pushed twice: first to queue its
+                // operands, and again, marked done, to come after them.
+                let mut stack = vec![(*root, false)];
+                while let Some((id, done)) = stack.pop() {
+                    if done {
+                        used.push(id);
+                        continue;
+                    }
+                    if !seen.insert(id) {
+                        continue;
+                    }
+                    stack.push((id, true));
+                    let operands = self.exprs[id].operands();
+                    stack.extend(operands.into_iter().rev().map(|e| (e, false)));
+                }
+            }
+        }
+        used
+    }
+
+    /// Call `f` on the immediates of every memory access still used by this
+    /// function, atomic or not, allowing them to be rewritten in place. See
+    /// `used_exprs`.
+    pub fn for_each_mem_arg_mut<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&mut MemArg),
+    {
+        for id in self.used_exprs() {
+            match &mut self.exprs[id] {
+                Expr::Load { arg, .. }
+                | Expr::Store { arg, .. }
+                | Expr::AtomicRmw { arg, .. }
+                | Expr::Cmpxchg { arg, .. }
+                | Expr::AtomicWait { arg, .. }
+                | Expr::AtomicNotify { arg, .. } => f(arg),
+                _ => {}
+            }
+        }
+    }
+
+    /// Does evaluating the given expression have any side effects?
+    ///
+    /// This is conservative: anything that writes state, transfers control,
+    /// or might trap is considered side-effecting.
+    pub fn has_side_effects(&self, expr: ExprId) -> bool {
+        match &self.exprs[expr] {
+            // Reading a global, mutable or not, has no side effects.
+            Expr::GetLocal { .. } | Expr::GetGlobal { .. } | Expr::I32Const(_) | Expr::Phi => false,
+            Expr::I32Add(lhs, rhs)
+            | Expr::I32Sub(lhs, rhs)
+            | Expr::I32Mul(lhs, rhs)
+            | Expr::I32Eq(lhs, rhs)
+            | Expr::I32And(lhs, rhs) => self.has_side_effects(*lhs) || self.has_side_effects(*rhs),
+            Expr::I32Eqz(e) | Expr::I32Popcnt(e) => self.has_side_effects(*e),
+            Expr::Splat { value: e, .. }
+            | Expr::ExtractLane { vector: e, .. }
+            | Expr::VecUnop { value: e, .. } => self.has_side_effects(*e),
+            Expr::ReplaceLane {
+                vector: lhs,
+                value: rhs,
+                ..
+            }
+            | Expr::VecBinop { lhs, rhs, .. }
+            | Expr::Shuffle { lhs, rhs, .. } => {
+                self.has_side_effects(*lhs) || self.has_side_effects(*rhs)
+            }
+            Expr::Select {
+                condition,
+                consequent,
+                alternative,
+            } => {
+                self.has_side_effects(*condition)
+                    || self.has_side_effects(*consequent)
+                    || self.has_side_effects(*alternative)
+            }
+            // Division and remainder trap on a zero divisor.
+            Expr::I32DivS(..) | Expr::I32DivU(..) | Expr::I32RemS(..) | Expr::I32RemU(..) => true,
+            // Loads trap when out of bounds.
+            Expr::Load { .. } => true,
+            Expr::SetLocal { .. }
+            | Expr::SetGlobal { .. }
+            | Expr::Store { .. }
+            | Expr::AtomicRmw { .. }
+            | Expr::Cmpxchg { .. }
+            | Expr::AtomicWait { .. }
+            | Expr::AtomicNotify { .. }
+            | Expr::Unreachable
+            | Expr::Br { .. }
+            | Expr::BrIf { .. }
+            | Expr::IfElse { .. }
+            | Expr::BrTable { .. }
+            | Expr::Drop(_)
+            | Expr::Return { .. } => true,
+        }
+    }
 }
 
 impl fmt::Display for Function {
@@ -140,10 +518,32 @@ impl Dot for Function {
     fn dot(&self, out: &mut Write) -> io::Result<()> {
         writeln!(out, "digraph {{")?;
         writeln!(out, "rankdir=LR;")?;
-        for expr in &self.exprs {
+        if let Some(name) = &self.name {
+            writeln!(out, "label=<{}>;", escape_html(name))?;
+        }
+        // Passes can leave blocks that were merged away empty, and
+        // expressions that no block uses any more, in the arenas. Only show
+        // what is still part of the function.
+        let blocks: Vec<_> = self
+            .blocks
+            .iter()
+            .filter(|(_, block)| !block.exprs.is_empty())
+            .collect();
+        let mut seen = HashSet::new();
+        let mut stack: Vec<ExprId> = blocks
+            .iter()
+            .flat_map(|(_, block)| block.exprs.iter().cloned())
+            .collect();
+        while let Some(id) = stack.pop() {
+            if !seen.insert(id) {
+                continue;
+            }
+            stack.extend(self.exprs[id].operands());
+        }
+        for expr in self.exprs.iter().filter(|(id, _)| seen.contains(id)) {
             expr.dot(out)?;
         }
-        for block in &self.blocks {
+        for block in blocks {
             block.dot(out)?;
         }
         writeln!(out, "}}")
@@ -182,20 +582,268 @@ macro_rules! testop {
     };
 }
 
+macro_rules! load {
+    ($ctx:ident, $kind:expr, $flags:expr, $offset:expr) => {
+        load!($ctx, $kind, $flags, $offset, false);
+    };
+    ($ctx:ident, $kind:expr, $flags:expr, $offset:expr, $atomic:expr) => {
+        let kind = $kind;
+        let atomic = $atomic;
+        let name = access_name(&kind, atomic);
+        let arg = mem_arg($ctx, &name, kind.width(), $flags, $offset, atomic)?;
+        let (_, address) = $ctx.pop_operand_expected(Some(ValType::I32))?;
+        let expr = $ctx.func.exprs.alloc(Expr::Load {
+            kind,
+            atomic,
+            arg,
+            address,
+        });
+        $ctx.push_operand(Some(kind.result_type()), expr);
+    };
+}
+
+macro_rules! store {
+    ($ctx:ident, $kind:expr, $flags:expr, $offset:expr) => {
+        store!($ctx, $kind, $flags, $offset, false);
+    };
+    ($ctx:ident, $kind:expr, $flags:expr, $offset:expr, $atomic:expr) => {
+        let kind = $kind;
+        let atomic = $atomic;
+        let name = access_name(&kind, atomic);
+        let arg = mem_arg($ctx, &name, kind.width(), $flags, $offset, atomic)?;
+        let (_, value) = $ctx.pop_operand_expected(Some(kind.value_type()))?;
+        let (_, address) = $ctx.pop_operand_expected(Some(ValType::I32))?;
+        let expr = $ctx.func.exprs.alloc(Expr::Store {
+            kind,
+            atomic,
+            arg,
+            address,
+            value,
+        });
+        $ctx.add_to_current_frame_block(expr);
+    };
+}
+
+macro_rules! atomic_rmw {
+    ($ctx:ident, $op:ident, $width:ident, $arg:expr) => {
+        let (op, width) = (AtomicOp::$op, AtomicWidth::$width);
+        let name = format!("{}.{}", width, op);
+        let arg = mem_arg(
+            $ctx,
+            &name,
+            width.width(),
+            $arg.align.into(),
+            $arg.offset,
+            true,
+        )?;
+        let (_, value) = $ctx.pop_operand_expected(Some(width.value_type()))?;
+        let (_, address) = $ctx.pop_operand_expected(Some(ValType::I32))?;
+        let expr = $ctx.func.exprs.alloc(Expr::AtomicRmw {
+            op,
+            width,
+            arg,
+            address,
+            value,
+        });
+        $ctx.push_operand(Some(width.value_type()), expr);
+    };
+}
+
+macro_rules! cmpxchg {
+    ($ctx:ident, $width:ident, $arg:expr) => {
+        let width = AtomicWidth::$width;
+        let name = format!("{}.cmpxchg", width);
+        let arg = mem_arg(
+            $ctx,
+            &name,
+            width.width(),
+            $arg.align.into(),
+            $arg.offset,
+            true,
+        )?;
+        let (_, replacement) = $ctx.pop_operand_expected(Some(width.value_type()))?;
+        let (_, expected) = $ctx.pop_operand_expected(Some(width.value_type()))?;
+        let (_, address) = $ctx.pop_operand_expected(Some(ValType::I32))?;
+        let expr = $ctx.func.exprs.alloc(Expr::Cmpxchg {
+            width,
+            arg,
+            address,
+            expected,
+            replacement,
+        });
+        $ctx.push_operand(Some(width.value_type()), expr);
+    };
+}
+
+macro_rules! atomic_wait {
+    ($ctx:ident, $ty:ident, $arg:expr) => {
+        let ty = ValType::$ty;
+        let (sixty_four, width) = match ty {
+            ValType::I64 => (true, 8),
+            _ => (false, 4),
+        };
+        let name = format!("{}.atomic.wait", ty);
+        let arg = mem_arg($ctx, &name, width, $arg.align.into(), $arg.offset, true)?;
+        let (_, timeout) = $ctx.pop_operand_expected(Some(ValType::I64))?;
+        let (_, expected) = $ctx.pop_operand_expected(Some(ty))?;
+        let (_, address) = $ctx.pop_operand_expected(Some(ValType::I32))?;
+        let expr = $ctx.func.exprs.alloc(Expr::AtomicWait {
+            sixty_four,
+            arg,
+            address,
+            expected,
+            timeout,
+        });
+        $ctx.push_operand(Some(ValType::I32), expr);
+    };
+}
+
+/// Validate a memory access's immediates: there must be a memory to access,
+/// and the alignment must not be larger than the access's natural alignment.
+/// Atomic accesses must be exactly naturally aligned.
+fn mem_arg(
+    ctx: &FunctionContext,
+    op: &dyn fmt::Display,
+    width: u32,
+    flags: u32,
+    offset: u32,
+    atomic: bool,
+) -> Result<MemArg> {
+    ctx.validation
+        .memory(0)
+        .with_context(|_| format!("`{}` without a memory", op))?;
+    if atomic && (flags >= 32 || 1 << flags != width) {
+        return Err(ErrorKind::InvalidWasm
+            .context(format!(
+                "alignment 2^{} of `{}` is not its natural alignment of {} bytes",
+                flags, op, width
+            ))
+            .into());
+    }
+    if flags >= 32 || 1 << flags > width {
+        return Err(ErrorKind::InvalidWasm
+            .context(format!(
+                "alignment 2^{} of `{}` is larger than its natural alignment of {} bytes",
+                flags, op, width
+            ))
+            .into());
+    }
+    Ok(MemArg {
+        align: 1 << flags,
+        offset,
+    })
+}
+
+macro_rules! splat {
+    ($ctx:ident, $shape:ident) => {
+        let (_, value) = $ctx.pop_operand_expected(Some(VecShape::$shape.lane_type()))?;
+        let expr = $ctx.func.exprs.alloc(Expr::Splat {
+            shape: VecShape::$shape,
+            value,
+        });
+        $ctx.push_operand(Some(ValType::V128), expr);
+    };
+}
+
+macro_rules! extract_lane {
+    ($ctx:ident, $shape:ident, $signed:expr, $lane:expr) => {
+        let shape = VecShape::$shape;
+        let lane = validate_lane(shape, $lane)?;
+        let (_, vector) = $ctx.pop_operand_expected(Some(ValType::V128))?;
+        let expr = $ctx.func.exprs.alloc(Expr::ExtractLane {
+            shape,
+            signed: $signed,
+            lane,
+            vector,
+        });
+        $ctx.push_operand(Some(shape.lane_type()), expr);
+    };
+}
+
+macro_rules! replace_lane {
+    ($ctx:ident, $shape:ident, $lane:expr) => {
+        let shape = VecShape::$shape;
+        let lane = validate_lane(shape, $lane)?;
+        let (_, value) = $ctx.pop_operand_expected(Some(shape.lane_type()))?;
+        let (_, vector) = $ctx.pop_operand_expected(Some(ValType::V128))?;
+        let expr = $ctx.func.exprs.alloc(Expr::ReplaceLane {
+            shape,
+            lane,
+            vector,
+            value,
+        });
+        $ctx.push_operand(Some(ValType::V128), expr);
+    };
+}
+
+macro_rules! vec_binop {
+    ($ctx:ident, $shape:ident, $op:ident) => {
+        let (_, rhs) = $ctx.pop_operand_expected(Some(ValType::V128))?;
+        let (_, lhs) = $ctx.pop_operand_expected(Some(ValType::V128))?;
+        let expr = $ctx.func.exprs.alloc(Expr::VecBinop {
+            shape: VecShape::$shape,
+            op: VecBinaryOp::$op,
+            lhs,
+            rhs,
+        });
+        $ctx.push_operand(Some(ValType::V128), expr);
+    };
+}
+
+macro_rules! vec_unop {
+    ($ctx:ident, $shape:ident, $op:ident) => {
+        let (_, value) = $ctx.pop_operand_expected(Some(ValType::V128))?;
+        let expr = $ctx.func.exprs.alloc(Expr::VecUnop {
+            shape: VecShape::$shape,
+            op: VecUnaryOp::$op,
+            value,
+        });
+        $ctx.push_operand(Some(ValType::V128), expr);
+    };
+}
+
+/// Check that a lane index is in bounds for the given vector shape.
+fn validate_lane(shape: VecShape, lane: u8) -> Result<u8> {
+    if lane < shape.lanes() {
+        Ok(lane)
+    } else {
+        Err(ErrorKind::InvalidWasm
+            .context(format!(
+                "lane index {} is out of bounds for {}, which has {} lanes",
+                lane,
+                shape,
+                shape.lanes()
+            ))
+            .into())
+    }
+}
+
 fn validate_instruction_sequence<'a>(
     ctx: &mut FunctionContext,
     insts: &'a [Instruction],
     until: Instruction,
 ) -> Result<&'a [Instruction]> {
+    let (rest, _) = validate_instruction_sequence_until_any(ctx, insts, &[until])?;
+    Ok(rest)
+}
+
+/// Like `validate_instruction_sequence` but stops at the first of any of the
+/// given instructions, and also returns which one was found.
+fn validate_instruction_sequence_until_any<'a>(
+    ctx: &mut FunctionContext,
+    insts: &'a [Instruction],
+    until: &[Instruction],
+) -> Result<(&'a [Instruction], &'a Instruction)> {
     let mut insts = insts;
     loop {
         match insts.first() {
             None => {
+                let expected: Vec<_> = until.iter().map(|i| format!("`{}`", i)).collect();
                 return Err(ErrorKind::InvalidWasm
-                    .context(format!("expected `{}`", until))
-                    .into())
+                    .context(format!("expected {}", expected.join(" or ")))
+                    .into());
             }
-            Some(inst) if inst == &until => return Ok(&insts[1..]),
+            Some(inst) if until.contains(inst) => return Ok((&insts[1..], inst)),
             Some(_) => {
                 insts = validate_instruction(ctx, insts)?;
             }
@@ -203,28 +851,192 @@ fn validate_instruction_sequence<'a>(
     }
 }
 
-fn validate_expression(
-    ctx: &mut FunctionContext,
-    expr: &[Instruction],
-    continuation: BlockId,
-) -> Result<Vec<ExprId>> {
-    let rest = validate_instruction_sequence(ctx, expr, Instruction::End)?;
+/// Create the function's entry and exit blocks, and push the control frame
+/// for its body. Returns the exit block.
+fn begin_function(ctx: &mut FunctionContext, result: &[ValType]) -> BlockId {
+    let func_exit = ctx.func.blocks.alloc(Block::new(
+        "function exit",
+        result.to_vec().into_boxed_slice(),
+    ));
+    ctx.func.exit_block = Some(func_exit);
+
+    let func_entry = ctx.push_control("function entry", vec![], result.to_vec(), func_exit);
+    ctx.func.entry_block = Some(func_entry);
+    func_exit
+}
+
+/// Pop the control frame for the function's body at its final `end`, and
+/// return its results from the exit block.
+fn end_function(ctx: &mut FunctionContext, func_exit: BlockId) -> Result<()> {
     let block = ctx.control(0).block;
-    let exprs = validate_end(ctx)?;
-    if rest.is_empty() {
-        ctx.func.finish_block(
+    let values = validate_end(ctx)?;
+    ctx.func.finish_block(
+        block,
+        Expr::Br {
+            block: func_exit,
+            args: values.clone().into_boxed_slice(),
+        },
+    );
+    ctx.func.finish_block(
+        func_exit,
+        Expr::Return {
+            values: values.into_boxed_slice(),
+        },
+    );
+    Ok(())
+}
+
+/// Open a `block`, returning its continuation.
+fn begin_block(ctx: &mut FunctionContext, block_ty: &elements::BlockType) -> BlockId {
+    let entry_block = ctx.control(0).block;
+
+    let params = ValType::from_block_ty(block_ty);
+    let continuation = ctx.func.blocks.alloc(Block::new(
+        "block continuation",
+        params.clone().into_boxed_slice(),
+    ));
+
+    let block = ctx.push_control("block", params.clone(), params, continuation);
+
+    ctx.func.finish_block(
+        entry_block,
+        Expr::Br {
+            block,
+            args: vec![].into_boxed_slice(),
+        },
+    );
+    continuation
+}
+
+/// Close the innermost `block` at its `end`.
+fn end_block(ctx: &mut FunctionContext, continuation: BlockId) -> Result<()> {
+    // Nested control instructions move the frame on to their continuation
+    // blocks, so the block that needs finishing is the frame's current one,
+    // not necessarily the one that was opened.
+    let end_block = ctx.control(0).block;
+    let values = validate_end(ctx)?;
+
+    ctx.func.finish_block(
+        end_block,
+        Expr::Br {
+            block: continuation,
+            args: values.into_boxed_slice(),
+        },
+    );
+    Ok(())
+}
+
+/// Open a `loop`, returning the loop's block.
+fn begin_loop(ctx: &mut FunctionContext, block_ty: &elements::BlockType) -> BlockId {
+    let entry = ctx.control(0).block;
+
+    let t = ValType::from_block_ty(block_ty);
+    let continuation = ctx.func.blocks.alloc(Block::new(
+        "post-loop continuation block",
+        t.clone().into_boxed_slice(),
+    ));
+
+    let block = ctx.push_control("loop", vec![], t, continuation);
+
+    let expr = ctx.func.exprs.alloc(Expr::Br {
+        block,
+        args: vec![].into_boxed_slice(),
+    });
+    ctx.add_to_block(entry, expr);
+    block
+}
+
+/// Close the innermost `loop` at its `end`.
+fn end_loop(ctx: &mut FunctionContext, block: BlockId) -> Result<()> {
+    let end_block = ctx.control(0).block;
+    validate_end(ctx)?;
+
+    // The loop block branches back to itself.
+    ctx.func.finish_block(
+        end_block,
+        Expr::Br {
             block,
-            Expr::Br {
-                block: continuation,
-                args: exprs.clone().into_boxed_slice(),
-            },
-        );
-        Ok(exprs)
+            args: vec![].into_boxed_slice(),
+        },
+    );
+    Ok(())
+}
+
+/// What is needed to finish an `if` after its consequent.
+#[derive(Clone, Copy, Debug)]
+struct IfFrame {
+    entry_block: BlockId,
+    condition: ExprId,
+    consequent: BlockId,
+    continuation: BlockId,
+}
+
+/// Open an `if`, popping its condition, and start its consequent.
+fn begin_if(ctx: &mut FunctionContext, block_ty: &elements::BlockType) -> Result<IfFrame> {
+    let (_, condition) = ctx.pop_operand_expected(Some(ValType::I32))?;
+
+    let entry_block = ctx.control(0).block;
+
+    let ty = ValType::from_block_ty(block_ty);
+    let continuation = ctx.func.blocks.alloc(Block::new(
+        "if/else continuation",
+        ty.clone().into_boxed_slice(),
+    ));
+    let consequent = ctx.push_control("consequent", ty.clone(), ty, continuation);
+
+    Ok(IfFrame {
+        entry_block,
+        condition,
+        consequent,
+        continuation,
+    })
+}
+
+/// Close an `if`'s consequent, at its `else` or at its `end` if it has no
+/// `else`, and start its alternative.
+fn begin_else(ctx: &mut FunctionContext, frame: &IfFrame) -> Result<()> {
+    let consequent_end = ctx.control(0).block;
+    let (results, values) = ctx.pop_control()?;
+
+    ctx.func.finish_block(
+        consequent_end,
+        Expr::Br {
+            block: frame.continuation,
+            args: values.into_boxed_slice(),
+        },
+    );
+
+    let alternative = ctx.push_control("alternative", results.clone(), results, frame.continuation);
+
+    let expr = ctx.func.exprs.alloc(Expr::IfElse {
+        condition: frame.condition,
+        consequent: frame.consequent,
+        alternative,
+    });
+
+    ctx.add_to_block(frame.entry_block, expr);
+    Ok(())
+}
+
+/// Close an `if`'s alternative at its `end`.
+fn end_if(ctx: &mut FunctionContext, frame: &IfFrame, has_else: bool) -> Result<()> {
+    // An `if` without an `else` still gets an (empty) alternative block,
+    // which only type checks when the `if` has no results.
+    let alternative_end = ctx.control(0).block;
+    let values = if has_else {
+        validate_end(ctx)?
     } else {
-        Err(ErrorKind::InvalidWasm
-            .context("trailing instructions after final `end`")
-            .into())
-    }
+        validate_end(ctx).context("`if` without an `else` cannot produce results")?
+    };
+
+    ctx.func.finish_block(
+        alternative_end,
+        Expr::Br {
+            block: frame.continuation,
+            args: values.into_boxed_slice(),
+        },
+    );
+    Ok(())
 }
 
 fn validate_end(ctx: &mut FunctionContext) -> Result<Vec<ExprId>> {
@@ -242,6 +1054,36 @@ fn validate_instruction<'a>(
     insts: &'a [Instruction],
 ) -> Result<&'a [Instruction]> {
     assert!(!insts.is_empty());
+
+    let first_new_expr = ctx.func.exprs.len();
+    let stack = ctx.operands.last().map(|(_, _, entry)| *entry);
+    // `insts` is always a suffix of the function body.
+    let offset = ctx
+        .offsets
+        .len()
+        .checked_sub(insts.len())
+        .map(|i| ctx.offsets[i]);
+
+    let rest = validate_single_instruction(ctx, insts)?;
+
+    // Expressions created by instructions nested inside a `block`, `loop`, or
+    // `if` have already been recorded, and keep the more precise record.
+    let len = ctx.func.exprs.len();
+    let provenance = &mut ctx.func.provenance;
+    provenance.resize(len, None);
+    for p in &mut provenance[first_new_expr..] {
+        if p.is_none() {
+            *p = Some(Provenance { offset, stack });
+        }
+    }
+
+    Ok(rest)
+}
+
+fn validate_single_instruction<'a>(
+    ctx: &mut FunctionContext,
+    insts: &'a [Instruction],
+) -> Result<&'a [Instruction]> {
     match &insts[0] {
         Instruction::GetLocal(n) => {
             let ty = ctx.validation.local(*n).context("invalid get_local")?;
@@ -258,6 +1100,28 @@ fn validate_instruction<'a>(
             });
             ctx.add_to_current_frame_block(expr);
         }
+        Instruction::GetGlobal(n) => {
+            let global = ctx.validation.global(*n).context("invalid get_global")?;
+            let ty = ValType::from(&global.content_type());
+            let expr = ctx.func.exprs.alloc(Expr::GetGlobal { ty, global: *n });
+            ctx.push_operand(Some(ty), expr);
+        }
+        Instruction::SetGlobal(n) => {
+            let global = ctx.validation.global(*n).context("invalid set_global")?;
+            if !global.is_mutable() {
+                return Err(ErrorKind::InvalidWasm
+                    .context(format!("set_global of immutable global {}", n))
+                    .into());
+            }
+            let ty = ValType::from(&global.content_type());
+            let (_, value) = ctx.pop_operand_expected(Some(ty))?;
+            let expr = ctx.func.exprs.alloc(Expr::SetGlobal {
+                ty,
+                global: *n,
+                value,
+            });
+            ctx.add_to_current_frame_block(expr);
+        }
         Instruction::I32Const(n) => {
             const_!(ctx, I32Const, I32, *n);
         }
@@ -270,12 +1134,539 @@ fn validate_instruction<'a>(
         Instruction::I32Mul => {
             binop!(ctx, I32Mul, I32);
         }
+        Instruction::I32DivS => {
+            binop!(ctx, I32DivS, I32);
+        }
+        Instruction::I32DivU => {
+            binop!(ctx, I32DivU, I32);
+        }
+        Instruction::I32RemS => {
+            binop!(ctx, I32RemS, I32);
+        }
+        Instruction::I32RemU => {
+            binop!(ctx, I32RemU, I32);
+        }
         Instruction::I32Eqz => {
             testop!(ctx, I32Eqz, I32);
         }
+        Instruction::I32Eq => {
+            binop!(ctx, I32Eq, I32);
+        }
+        Instruction::I32And => {
+            binop!(ctx, I32And, I32);
+        }
         Instruction::I32Popcnt => {
             unop!(ctx, I32Popcnt, I32);
         }
+        Instruction::I32Load(flags, offset) => {
+            load!(ctx, LoadKind::I32, *flags, *offset);
+        }
+        Instruction::I64Load(flags, offset) => {
+            load!(ctx, LoadKind::I64, *flags, *offset);
+        }
+        Instruction::F32Load(flags, offset) => {
+            load!(ctx, LoadKind::F32, *flags, *offset);
+        }
+        Instruction::F64Load(flags, offset) => {
+            load!(ctx, LoadKind::F64, *flags, *offset);
+        }
+        Instruction::I32Load8S(flags, offset) => {
+            load!(ctx, LoadKind::I32_8 { signed: true }, *flags, *offset);
+        }
+        Instruction::I32Load8U(flags, offset) => {
+            load!(ctx, LoadKind::I32_8 { signed: false }, *flags, *offset);
+        }
+        Instruction::I32Load16S(flags, offset) => {
+            load!(ctx, LoadKind::I32_16 { signed: true }, *flags, *offset);
+        }
+        Instruction::I32Load16U(flags, offset) => {
+            load!(ctx, LoadKind::I32_16 { signed: false }, *flags, *offset);
+        }
+        Instruction::I64Load8S(flags, offset) => {
+            load!(ctx, LoadKind::I64_8 { signed: true }, *flags, *offset);
+        }
+        Instruction::I64Load8U(flags, offset) => {
+            load!(ctx, LoadKind::I64_8 { signed: false }, *flags, *offset);
+        }
+        Instruction::I64Load16S(flags, offset) => {
+            load!(ctx, LoadKind::I64_16 { signed: true }, *flags, *offset);
+        }
+        Instruction::I64Load16U(flags, offset) => {
+            load!(ctx, LoadKind::I64_16 { signed: false }, *flags, *offset);
+        }
+        Instruction::I64Load32S(flags, offset) => {
+            load!(ctx, LoadKind::I64_32 { signed: true }, *flags, *offset);
+        }
+        Instruction::I64Load32U(flags, offset) => {
+            load!(ctx, LoadKind::I64_32 { signed: false }, *flags, *offset);
+        }
+        Instruction::I32Store(flags, offset) => {
+            store!(ctx, StoreKind::I32, *flags, *offset);
+        }
+        Instruction::I64Store(flags, offset) => {
+            store!(ctx, StoreKind::I64, *flags, *offset);
+        }
+        Instruction::F32Store(flags, offset) => {
+            store!(ctx, StoreKind::F32, *flags, *offset);
+        }
+        Instruction::F64Store(flags, offset) => {
+            store!(ctx, StoreKind::F64, *flags, *offset);
+        }
+        Instruction::I32Store8(flags, offset) => {
+            store!(ctx, StoreKind::I32_8, *flags, *offset);
+        }
+        Instruction::I32Store16(flags, offset) => {
+            store!(ctx, StoreKind::I32_16, *flags, *offset);
+        }
+        Instruction::I64Store8(flags, offset) => {
+            store!(ctx, StoreKind::I64_8, *flags, *offset);
+        }
+        Instruction::I64Store16(flags, offset) => {
+            store!(ctx, StoreKind::I64_16, *flags, *offset);
+        }
+        Instruction::I64Store32(flags, offset) => {
+            store!(ctx, StoreKind::I64_32, *flags, *offset);
+        }
+        Instruction::I32AtomicLoad(m) => {
+            load!(ctx, LoadKind::I32, m.align.into(), m.offset, true);
+        }
+        Instruction::I64AtomicLoad(m) => {
+            load!(ctx, LoadKind::I64, m.align.into(), m.offset, true);
+        }
+        Instruction::I32AtomicLoad8u(m) => {
+            load!(
+                ctx,
+                LoadKind::I32_8 { signed: false },
+                m.align.into(),
+                m.offset,
+                true
+            );
+        }
+        Instruction::I32AtomicLoad16u(m) => {
+            load!(
+                ctx,
+                LoadKind::I32_16 { signed: false },
+                m.align.into(),
+                m.offset,
+                true
+            );
+        }
+        Instruction::I64AtomicLoad8u(m) => {
+            load!(
+                ctx,
+                LoadKind::I64_8 { signed: false },
+                m.align.into(),
+                m.offset,
+                true
+            );
+        }
+        Instruction::I64AtomicLoad16u(m) => {
+            load!(
+                ctx,
+                LoadKind::I64_16 { signed: false },
+                m.align.into(),
+                m.offset,
+                true
+            );
+        }
+        Instruction::I64AtomicLoad32u(m) => {
+            load!(
+                ctx,
+                LoadKind::I64_32 { signed: false },
+                m.align.into(),
+                m.offset,
+                true
+            );
+        }
+        Instruction::I32AtomicStore(m) => {
+            store!(ctx, StoreKind::I32, m.align.into(), m.offset, true);
+        }
+        Instruction::I64AtomicStore(m) => {
+            store!(ctx, StoreKind::I64, m.align.into(), m.offset, true);
+        }
+        Instruction::I32AtomicStore8u(m) => {
+            store!(ctx, StoreKind::I32_8, m.align.into(), m.offset, true);
+        }
+        Instruction::I32AtomicStore16u(m) => {
+            store!(ctx, StoreKind::I32_16, m.align.into(), m.offset, true);
+        }
+        Instruction::I64AtomicStore8u(m) => {
+            store!(ctx, StoreKind::I64_8, m.align.into(), m.offset, true);
+        }
+        Instruction::I64AtomicStore16u(m) => {
+            store!(ctx, StoreKind::I64_16, m.align.into(), m.offset, true);
+        }
+        Instruction::I64AtomicStore32u(m) => {
+            store!(ctx, StoreKind::I64_32, m.align.into(), m.offset, true);
+        }
+        Instruction::I32AtomicRmwAdd(m) => {
+            atomic_rmw!(ctx, Add, I32, m);
+        }
+        Instruction::I64AtomicRmwAdd(m) => {
+            atomic_rmw!(ctx, Add, I64, m);
+        }
+        Instruction::I32AtomicRmwAdd8u(m) => {
+            atomic_rmw!(ctx, Add, I32_8, m);
+        }
+        Instruction::I32AtomicRmwAdd16u(m) => {
+            atomic_rmw!(ctx, Add, I32_16, m);
+        }
+        Instruction::I64AtomicRmwAdd8u(m) => {
+            atomic_rmw!(ctx, Add, I64_8, m);
+        }
+        Instruction::I64AtomicRmwAdd16u(m) => {
+            atomic_rmw!(ctx, Add, I64_16, m);
+        }
+        Instruction::I64AtomicRmwAdd32u(m) => {
+            atomic_rmw!(ctx, Add, I64_32, m);
+        }
+        Instruction::I32AtomicRmwSub(m) => {
+            atomic_rmw!(ctx, Sub, I32, m);
+        }
+        Instruction::I64AtomicRmwSub(m) => {
+            atomic_rmw!(ctx, Sub, I64, m);
+        }
+        Instruction::I32AtomicRmwSub8u(m) => {
+            atomic_rmw!(ctx, Sub, I32_8, m);
+        }
+        Instruction::I32AtomicRmwSub16u(m) => {
+            atomic_rmw!(ctx, Sub, I32_16, m);
+        }
+        Instruction::I64AtomicRmwSub8u(m) => {
+            atomic_rmw!(ctx, Sub, I64_8, m);
+        }
+        Instruction::I64AtomicRmwSub16u(m) => {
+            atomic_rmw!(ctx, Sub, I64_16, m);
+        }
+        Instruction::I64AtomicRmwSub32u(m) => {
+            atomic_rmw!(ctx, Sub, I64_32, m);
+        }
+        Instruction::I32AtomicRmwAnd(m) => {
+            atomic_rmw!(ctx, And, I32, m);
+        }
+        Instruction::I64AtomicRmwAnd(m) => {
+            atomic_rmw!(ctx, And, I64, m);
+        }
+        Instruction::I32AtomicRmwAnd8u(m) => {
+            atomic_rmw!(ctx, And, I32_8, m);
+        }
+        Instruction::I32AtomicRmwAnd16u(m) => {
+            atomic_rmw!(ctx, And, I32_16, m);
+        }
+        Instruction::I64AtomicRmwAnd8u(m) => {
+            atomic_rmw!(ctx, And, I64_8, m);
+        }
+        Instruction::I64AtomicRmwAnd16u(m) => {
+            atomic_rmw!(ctx, And, I64_16, m);
+        }
+        Instruction::I64AtomicRmwAnd32u(m) => {
+            atomic_rmw!(ctx, And, I64_32, m);
+        }
+        Instruction::I32AtomicRmwOr(m) => {
+            atomic_rmw!(ctx, Or, I32, m);
+        }
+        Instruction::I64AtomicRmwOr(m) => {
+            atomic_rmw!(ctx, Or, I64, m);
+        }
+        Instruction::I32AtomicRmwOr8u(m) => {
+            atomic_rmw!(ctx, Or, I32_8, m);
+        }
+        Instruction::I32AtomicRmwOr16u(m) => {
+            atomic_rmw!(ctx, Or, I32_16, m);
+        }
+        Instruction::I64AtomicRmwOr8u(m) => {
+            atomic_rmw!(ctx, Or, I64_8, m);
+        }
+        Instruction::I64AtomicRmwOr16u(m) => {
+            atomic_rmw!(ctx, Or, I64_16, m);
+        }
+        Instruction::I64AtomicRmwOr32u(m) => {
+            atomic_rmw!(ctx, Or, I64_32, m);
+        }
+        Instruction::I32AtomicRmwXor(m) => {
+            atomic_rmw!(ctx, Xor, I32, m);
+        }
+        Instruction::I64AtomicRmwXor(m) => {
+            atomic_rmw!(ctx, Xor, I64, m);
+        }
+        Instruction::I32AtomicRmwXor8u(m) => {
+            atomic_rmw!(ctx, Xor, I32_8, m);
+        }
+        Instruction::I32AtomicRmwXor16u(m) => {
+            atomic_rmw!(ctx, Xor, I32_16, m);
+        }
+        Instruction::I64AtomicRmwXor8u(m) => {
+            atomic_rmw!(ctx, Xor, I64_8, m);
+        }
+        Instruction::I64AtomicRmwXor16u(m) => {
+            atomic_rmw!(ctx, Xor, I64_16, m);
+        }
+        Instruction::I64AtomicRmwXor32u(m) => {
+            atomic_rmw!(ctx, Xor, I64_32, m);
+        }
+        Instruction::I32AtomicRmwXchg(m) => {
+            atomic_rmw!(ctx, Xchg, I32, m);
+        }
+        Instruction::I64AtomicRmwXchg(m) => {
+            atomic_rmw!(ctx, Xchg, I64, m);
+        }
+        Instruction::I32AtomicRmwXchg8u(m) => {
+            atomic_rmw!(ctx, Xchg, I32_8, m);
+        }
+        Instruction::I32AtomicRmwXchg16u(m) => {
+            atomic_rmw!(ctx, Xchg, I32_16, m);
+        }
+        Instruction::I64AtomicRmwXchg8u(m) => {
+            atomic_rmw!(ctx, Xchg, I64_8, m);
+        }
+        Instruction::I64AtomicRmwXchg16u(m) => {
+            atomic_rmw!(ctx, Xchg, I64_16, m);
+        }
+        Instruction::I64AtomicRmwXchg32u(m) => {
+            atomic_rmw!(ctx, Xchg, I64_32, m);
+        }
+        Instruction::I32AtomicRmwCmpxchg(m) => {
+            cmpxchg!(ctx, I32, m);
+        }
+        Instruction::I64AtomicRmwCmpxchg(m) => {
+            cmpxchg!(ctx, I64, m);
+        }
+        Instruction::I32AtomicRmwCmpxchg8u(m) => {
+            cmpxchg!(ctx, I32_8, m);
+        }
+        Instruction::I32AtomicRmwCmpxchg16u(m) => {
+            cmpxchg!(ctx, I32_16, m);
+        }
+        Instruction::I64AtomicRmwCmpxchg8u(m) => {
+            cmpxchg!(ctx, I64_8, m);
+        }
+        Instruction::I64AtomicRmwCmpxchg16u(m) => {
+            cmpxchg!(ctx, I64_16, m);
+        }
+        Instruction::I64AtomicRmwCmpxchg32u(m) => {
+            cmpxchg!(ctx, I64_32, m);
+        }
+        Instruction::I32AtomicWait(m) => {
+            atomic_wait!(ctx, I32, m);
+        }
+        Instruction::I64AtomicWait(m) => {
+            atomic_wait!(ctx, I64, m);
+        }
+        Instruction::AtomicWake(m) => {
+            let arg = mem_arg(ctx, &"atomic.notify", 4, m.align.into(), m.offset, true)?;
+            let (_, count) = ctx.pop_operand_expected(Some(ValType::I32))?;
+            let (_, address) = ctx.pop_operand_expected(Some(ValType::I32))?;
+            let expr = ctx.func.exprs.alloc(Expr::AtomicNotify {
+                arg,
+                address,
+                count,
+            });
+            ctx.push_operand(Some(ValType::I32), expr);
+        }
+        Instruction::I8x16Splat => {
+            splat!(ctx, I8x16);
+        }
+        Instruction::I16x8Splat => {
+            splat!(ctx, I16x8);
+        }
+        Instruction::I32x4Splat => {
+            splat!(ctx, I32x4);
+        }
+        Instruction::I64x2Splat => {
+            splat!(ctx, I64x2);
+        }
+        Instruction::F32x4Splat => {
+            splat!(ctx, F32x4);
+        }
+        Instruction::F64x2Splat => {
+            splat!(ctx, F64x2);
+        }
+        Instruction::I8x16ExtractLaneS(n) => {
+            extract_lane!(ctx, I8x16, true, *n);
+        }
+        Instruction::I8x16ExtractLaneU(n) => {
+            extract_lane!(ctx, I8x16, false, *n);
+        }
+        Instruction::I16x8ExtractLaneS(n) => {
+            extract_lane!(ctx, I16x8, true, *n);
+        }
+        Instruction::I16x8ExtractLaneU(n) => {
+            extract_lane!(ctx, I16x8, false, *n);
+        }
+        Instruction::I32x4ExtractLane(n) => {
+            extract_lane!(ctx, I32x4, false, *n);
+        }
+        Instruction::I64x2ExtractLane(n) => {
+            extract_lane!(ctx, I64x2, false, *n);
+        }
+        Instruction::F32x4ExtractLane(n) => {
+            extract_lane!(ctx, F32x4, false, *n);
+        }
+        Instruction::F64x2ExtractLane(n) => {
+            extract_lane!(ctx, F64x2, false, *n);
+        }
+        Instruction::I8x16ReplaceLane(n) => {
+            replace_lane!(ctx, I8x16, *n);
+        }
+        Instruction::I16x8ReplaceLane(n) => {
+            replace_lane!(ctx, I16x8, *n);
+        }
+        Instruction::I32x4ReplaceLane(n) => {
+            replace_lane!(ctx, I32x4, *n);
+        }
+        Instruction::I64x2ReplaceLane(n) => {
+            replace_lane!(ctx, I64x2, *n);
+        }
+        Instruction::F32x4ReplaceLane(n) => {
+            replace_lane!(ctx, F32x4, *n);
+        }
+        Instruction::F64x2ReplaceLane(n) => {
+            replace_lane!(ctx, F64x2, *n);
+        }
+        Instruction::V8x16Shuffle(lanes) => {
+            for lane in lanes.iter() {
+                if *lane >= 32 {
+                    return Err(ErrorKind::InvalidWasm
+                        .context(format!(
+                            "lane index {} of `v8x16.shuffle` is out of bounds, it must be less \
+                             than 32",
+                            lane
+                        ))
+                        .into());
+                }
+            }
+            let (_, rhs) = ctx.pop_operand_expected(Some(ValType::V128))?;
+            let (_, lhs) = ctx.pop_operand_expected(Some(ValType::V128))?;
+            let expr = ctx.func.exprs.alloc(Expr::Shuffle {
+                lanes: **lanes,
+                lhs,
+                rhs,
+            });
+            ctx.push_operand(Some(ValType::V128), expr);
+        }
+        Instruction::I8x16Add => {
+            vec_binop!(ctx, I8x16, Add);
+        }
+        Instruction::I8x16Sub => {
+            vec_binop!(ctx, I8x16, Sub);
+        }
+        Instruction::I16x8Add => {
+            vec_binop!(ctx, I16x8, Add);
+        }
+        Instruction::I16x8Sub => {
+            vec_binop!(ctx, I16x8, Sub);
+        }
+        Instruction::I32x4Add => {
+            vec_binop!(ctx, I32x4, Add);
+        }
+        Instruction::I32x4Sub => {
+            vec_binop!(ctx, I32x4, Sub);
+        }
+        Instruction::I64x2Add => {
+            vec_binop!(ctx, I64x2, Add);
+        }
+        Instruction::I64x2Sub => {
+            vec_binop!(ctx, I64x2, Sub);
+        }
+        Instruction::F32x4Add => {
+            vec_binop!(ctx, F32x4, Add);
+        }
+        Instruction::F32x4Sub => {
+            vec_binop!(ctx, F32x4, Sub);
+        }
+        Instruction::F64x2Add => {
+            vec_binop!(ctx, F64x2, Add);
+        }
+        Instruction::F64x2Sub => {
+            vec_binop!(ctx, F64x2, Sub);
+        }
+        Instruction::I8x16Mul => {
+            vec_binop!(ctx, I8x16, Mul);
+        }
+        Instruction::I16x8Mul => {
+            vec_binop!(ctx, I16x8, Mul);
+        }
+        Instruction::I32x4Mul => {
+            vec_binop!(ctx, I32x4, Mul);
+        }
+        Instruction::F32x4Mul => {
+            vec_binop!(ctx, F32x4, Mul);
+        }
+        Instruction::F64x2Mul => {
+            vec_binop!(ctx, F64x2, Mul);
+        }
+        Instruction::F32x4Div => {
+            vec_binop!(ctx, F32x4, Div);
+        }
+        Instruction::F32x4Min => {
+            vec_binop!(ctx, F32x4, Min);
+        }
+        Instruction::F32x4Max => {
+            vec_binop!(ctx, F32x4, Max);
+        }
+        Instruction::F64x2Div => {
+            vec_binop!(ctx, F64x2, Div);
+        }
+        Instruction::F64x2Min => {
+            vec_binop!(ctx, F64x2, Min);
+        }
+        Instruction::F64x2Max => {
+            vec_binop!(ctx, F64x2, Max);
+        }
+        Instruction::I8x16AddSaturateS => {
+            vec_binop!(ctx, I8x16, AddSaturateS);
+        }
+        Instruction::I8x16AddSaturateU => {
+            vec_binop!(ctx, I8x16, AddSaturateU);
+        }
+        Instruction::I8x16SubSaturateS => {
+            vec_binop!(ctx, I8x16, SubSaturateS);
+        }
+        Instruction::I8x16SubSaturateU => {
+            vec_binop!(ctx, I8x16, SubSaturateU);
+        }
+        Instruction::I16x8AddSaturateS => {
+            vec_binop!(ctx, I16x8, AddSaturateS);
+        }
+        Instruction::I16x8AddSaturateU => {
+            vec_binop!(ctx, I16x8, AddSaturateU);
+        }
+        Instruction::I16x8SubSaturateS => {
+            vec_binop!(ctx, I16x8, SubSaturateS);
+        }
+        Instruction::I16x8SubSaturateU => {
+            vec_binop!(ctx, I16x8, SubSaturateU);
+        }
+        Instruction::I8x16Neg => {
+            vec_unop!(ctx, I8x16, Neg);
+        }
+        Instruction::I16x8Neg => {
+            vec_unop!(ctx, I16x8, Neg);
+        }
+        Instruction::I32x4Neg => {
+            vec_unop!(ctx, I32x4, Neg);
+        }
+        Instruction::I64x2Neg => {
+            vec_unop!(ctx, I64x2, Neg);
+        }
+        Instruction::F32x4Neg => {
+            vec_unop!(ctx, F32x4, Neg);
+        }
+        Instruction::F64x2Neg => {
+            vec_unop!(ctx, F64x2, Neg);
+        }
+        Instruction::F32x4Abs => {
+            vec_unop!(ctx, F32x4, Abs);
+        }
+        Instruction::F32x4Sqrt => {
+            vec_unop!(ctx, F32x4, Sqrt);
+        }
+        Instruction::F64x2Abs => {
+            vec_unop!(ctx, F64x2, Abs);
+        }
+        Instruction::F64x2Sqrt => {
+            vec_unop!(ctx, F64x2, Sqrt);
+        }
         Instruction::Drop => {
             let (_, e) = ctx.pop_operand()?;
             let expr = ctx.func.exprs.alloc(Expr::Drop(e));
@@ -312,121 +1703,37 @@ fn validate_instruction<'a>(
         Instruction::Block(block_ty) => {
             let validation = ctx.validation.for_block(*block_ty);
             let mut ctx = ctx.nested(&validation);
-
-            let entry_block = ctx.control(0).block;
-
-            let params = ValType::from_block_ty(block_ty);
-            let continuation = ctx.func.blocks.alloc(Block::new(
-                "block continuation",
-                params.clone().into_boxed_slice(),
-            ));
-
-            let block = ctx.push_control("block", params.clone(), params, continuation);
-
-            ctx.func.finish_block(
-                entry_block,
-                Expr::Br {
-                    block,
-                    args: vec![].into_boxed_slice(),
-                },
-            );
-
+            let continuation = begin_block(&mut ctx, block_ty);
             let rest = validate_instruction_sequence(&mut ctx, &insts[1..], Instruction::End)?;
-            let values = validate_end(&mut ctx)?;
-
-            ctx.func.finish_block(
-                block,
-                Expr::Br {
-                    block: continuation,
-                    args: values.into_boxed_slice(),
-                },
-            );
-
+            end_block(&mut ctx, continuation)?;
             return Ok(rest);
         }
         Instruction::Loop(block_ty) => {
             let validation = ctx.validation.for_loop();
             let mut ctx = ctx.nested(&validation);
-
-            let entry = ctx.control(0).block;
-
-            let t = ValType::from_block_ty(block_ty);
-            let continuation = ctx.func.blocks.alloc(Block::new(
-                "post-loop continuation block",
-                t.clone().into_boxed_slice(),
-            ));
-
-            let block = ctx.push_control("loop", vec![], t, continuation);
-
-            let expr = ctx.func.exprs.alloc(Expr::Br {
-                block,
-                args: vec![].into_boxed_slice(),
-            });
-            ctx.add_to_block(entry, expr);
-
+            let block = begin_loop(&mut ctx, block_ty);
             let rest = validate_instruction_sequence(&mut ctx, &insts[1..], Instruction::End)?;
-            validate_end(&mut ctx)?;
-
-            // The loop block branches back to itself.
-            ctx.func.finish_block(
-                block,
-                Expr::Br {
-                    block,
-                    args: vec![].into_boxed_slice(),
-                },
-            );
-
+            end_loop(&mut ctx, block)?;
             return Ok(rest);
         }
         Instruction::If(block_ty) => {
             let validation = ctx.validation.for_if_else(*block_ty);
             let mut ctx = ctx.nested(&validation);
-
-            let (_, condition) = ctx.pop_operand_expected(Some(ValType::I32))?;
-
-            let entry_block = ctx.control(0).block;
-
-            let ty = ValType::from_block_ty(block_ty);
-            let continuation = ctx.func.blocks.alloc(Block::new(
-                "if/else continuation",
-                ty.clone().into_boxed_slice(),
-            ));
-            let consequent = ctx.push_control("consequent", ty.clone(), ty, continuation);
-
-            let rest = validate_instruction_sequence(&mut ctx, &insts[1..], Instruction::Else)?;
-            let (results, values) = ctx.pop_control()?;
-
-            ctx.func.finish_block(
-                consequent,
-                Expr::Br {
-                    block: continuation,
-                    args: values.into_boxed_slice(),
-                },
-            );
-
-            let alternative =
-                ctx.push_control("alternative", results.clone(), results, continuation);
-
-            let expr = ctx.func.exprs.alloc(Expr::IfElse {
-                condition,
-                consequent,
-                alternative,
-            });
-
-            ctx.add_to_block(entry_block, expr);
-
-            let rest_rest = validate_instruction_sequence(&mut ctx, rest, Instruction::End)?;
-            let values = validate_end(&mut ctx)?;
-
-            ctx.func.finish_block(
-                alternative,
-                Expr::Br {
-                    block: continuation,
-                    args: values.into_boxed_slice(),
-                },
-            );
-
-            return Ok(rest_rest);
+            let frame = begin_if(&mut ctx, block_ty)?;
+            let (rest, found) = validate_instruction_sequence_until_any(
+                &mut ctx,
+                &insts[1..],
+                &[Instruction::Else, Instruction::End],
+            )?;
+            begin_else(&mut ctx, &frame)?;
+            let has_else = *found == Instruction::Else;
+            let rest = if has_else {
+                validate_instruction_sequence(&mut ctx, rest, Instruction::End)?
+            } else {
+                rest
+            };
+            end_if(&mut ctx, &frame, has_else)?;
+            return Ok(rest);
         }
         Instruction::End => {
             unreachable!();
@@ -543,7 +1850,11 @@ fn validate_instruction<'a>(
             ctx.add_to_current_frame_block(expr);
         }
 
-        op => unimplemented!("Have not implemented support for opcode yet: {:?}", op),
+        op => {
+            return Err(ErrorKind::Unsupported
+                .context(format!("`{}` is not supported yet", op))
+                .into());
+        }
     }
 
     Ok(&insts[1..])
@@ -1,7 +1,18 @@
 //! Matching expressions.
+//!
+//! Every expression has a generated matcher, like `I32AddMatcher`, that is
+//! constructed from matchers for each of its operands. Together with the
+//! leaf matchers in this module, they describe patterns declaratively:
+//!
+//! ```
+//! use walrus::ir::matcher::{any, i32_const, I32AddMatcher};
+//!
+//! // Matches `x + 0` for any `x`.
+//! let add_zero = I32AddMatcher::new(any(), i32_const(0));
+//! ```
 
-use super::Expr;
 use super::super::function::Function;
+use super::{Expr, ExprId};
 
 // Re-export the custom derive-generated impls here, where it makes more sense
 // to expose them.
@@ -12,3 +23,85 @@ pub trait Matcher {
     /// Does this expression match?
     fn is_match(&self, func: &Function, expr: &Expr) -> bool;
 }
+
+/// Matches any expression.
+#[derive(Clone, Copy, Debug)]
+pub struct AnyMatcher;
+
+impl Matcher for AnyMatcher {
+    fn is_match(&self, _: &Function, _: &Expr) -> bool {
+        true
+    }
+}
+
+/// Match any expression.
+pub fn any() -> AnyMatcher {
+    AnyMatcher
+}
+
+/// Matches a `get_local` of a particular local.
+#[derive(Clone, Copy, Debug)]
+pub struct GetLocalOfMatcher {
+    local: u32,
+}
+
+impl Matcher for GetLocalOfMatcher {
+    fn is_match(&self, _: &Function, expr: &Expr) -> bool {
+        match expr {
+            Expr::GetLocal { local, .. } => *local == self.local,
+            _ => false,
+        }
+    }
+}
+
+/// Match a `get_local` of the given local.
+pub fn get_local(local: u32) -> GetLocalOfMatcher {
+    GetLocalOfMatcher { local }
+}
+
+/// Matches a particular `i32.const`.
+#[derive(Clone, Copy, Debug)]
+pub struct I32ConstOfMatcher {
+    value: i32,
+}
+
+impl Matcher for I32ConstOfMatcher {
+    fn is_match(&self, _: &Function, expr: &Expr) -> bool {
+        match expr {
+            Expr::I32Const(n) => *n == self.value,
+            _ => false,
+        }
+    }
+}
+
+/// Match an `i32.const` of the given value.
+pub fn i32_const(value: i32) -> I32ConstOfMatcher {
+    I32ConstOfMatcher { value }
+}
+
+/// Matches when either of two matchers does.
+#[derive(Clone, Copy, Debug)]
+pub struct OrMatcher<T, U> {
+    a: T,
+    b: U,
+}
+
+impl<T: Matcher, U: Matcher> Matcher for OrMatcher<T, U> {
+    fn is_match(&self, func: &Function, expr: &Expr) -> bool {
+        self.a.is_match(func, expr) || self.b.is_match(func, expr)
+    }
+}
+
+/// Match an expression that matches either `a` or `b`.
+pub fn or<T: Matcher, U: Matcher>(a: T, b: U) -> OrMatcher<T, U> {
+    OrMatcher { a, b }
+}
+
+/// Find every expression still used by the given function that the matcher
+/// matches. See `Function::used_exprs`.
+pub fn find<M: Matcher>(func: &Function, matcher: &M) -> Vec<ExprId> {
+    func.used_exprs()
+        .into_iter()
+        .filter(|id| matcher.is_match(func, &func.exprs[*id]))
+        .collect()
+}
@@ -1,10 +1,14 @@
 //! TODO
 
 pub mod matcher;
+mod value;
+
+pub use self::value::Value;
 
 use super::ValType;
 use id_arena::Id;
 use crate::dot::{Dot, Port};
+use std::fmt;
 use std::io::{self, Write};
 use walrus_derive::WalrusExpr;
 
@@ -45,6 +49,19 @@ impl Block {
             exprs: vec![],
         }
     }
+
+    /// The expressions in this block, in execution order.
+    pub fn exprs(&self) -> &[ExprId] {
+        &self.exprs
+    }
+
+    /// The types of the values this block takes as parameters. Every branch
+    /// to this block passes values of exactly these types, so for the
+    /// continuation of a `block` or `if` these are its result types, and for
+    /// the header of a `loop` they are the loop's label types.
+    pub fn params(&self) -> &[ValType] {
+        &self.params
+    }
 }
 
 impl<'a> Dot for (BlockId, &'a Block) {
@@ -84,6 +101,338 @@ impl<'a> Dot for (BlockId, &'a Block) {
     }
 }
 
+/// The shape of a `v128` value: how many lanes it is split into, and the type
+/// of each lane.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum VecShape {
+    /// Sixteen 8-bit integer lanes.
+    I8x16,
+    /// Eight 16-bit integer lanes.
+    I16x8,
+    /// Four 32-bit integer lanes.
+    I32x4,
+    /// Two 64-bit integer lanes.
+    I64x2,
+    /// Four 32-bit float lanes.
+    F32x4,
+    /// Two 64-bit float lanes.
+    F64x2,
+}
+
+impl VecShape {
+    /// The number of lanes in this shape.
+    pub fn lanes(&self) -> u8 {
+        match self {
+            VecShape::I8x16 => 16,
+            VecShape::I16x8 => 8,
+            VecShape::I32x4 | VecShape::F32x4 => 4,
+            VecShape::I64x2 | VecShape::F64x2 => 2,
+        }
+    }
+
+    /// The type of a single lane's value when it is on the stack. Lanes
+    /// narrower than 32 bits are extended to an `i32`.
+    pub fn lane_type(&self) -> ValType {
+        match self {
+            VecShape::I8x16 | VecShape::I16x8 | VecShape::I32x4 => ValType::I32,
+            VecShape::I64x2 => ValType::I64,
+            VecShape::F32x4 => ValType::F32,
+            VecShape::F64x2 => ValType::F64,
+        }
+    }
+}
+
+impl fmt::Display for VecShape {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                VecShape::I8x16 => "i8x16",
+                VecShape::I16x8 => "i16x8",
+                VecShape::I32x4 => "i32x4",
+                VecShape::I64x2 => "i64x2",
+                VecShape::F32x4 => "f32x4",
+                VecShape::F64x2 => "f64x2",
+            }
+        )
+    }
+}
+
+/// Lane-wise binary arithmetic on `v128` values.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub enum VecBinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Min,
+    Max,
+    AddSaturateS,
+    AddSaturateU,
+    SubSaturateS,
+    SubSaturateU,
+}
+
+impl fmt::Display for VecBinaryOp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                VecBinaryOp::Add => "add",
+                VecBinaryOp::Sub => "sub",
+                VecBinaryOp::Mul => "mul",
+                VecBinaryOp::Div => "div",
+                VecBinaryOp::Min => "min",
+                VecBinaryOp::Max => "max",
+                VecBinaryOp::AddSaturateS => "add_saturate_s",
+                VecBinaryOp::AddSaturateU => "add_saturate_u",
+                VecBinaryOp::SubSaturateS => "sub_saturate_s",
+                VecBinaryOp::SubSaturateU => "sub_saturate_u",
+            }
+        )
+    }
+}
+
+/// Lane-wise unary arithmetic on `v128` values.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub enum VecUnaryOp {
+    Neg,
+    Abs,
+    Sqrt,
+}
+
+impl fmt::Display for VecUnaryOp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                VecUnaryOp::Neg => "neg",
+                VecUnaryOp::Abs => "abs",
+                VecUnaryOp::Sqrt => "sqrt",
+            }
+        )
+    }
+}
+
+/// The immediate arguments of a memory access.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MemArg {
+    /// The alignment hint of the access, in bytes.
+    pub align: u32,
+    /// The constant offset added to the dynamic address.
+    pub offset: u32,
+}
+
+/// The kinds of memory loads.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub enum LoadKind {
+    I32,
+    I64,
+    F32,
+    F64,
+    I32_8 { signed: bool },
+    I32_16 { signed: bool },
+    I64_8 { signed: bool },
+    I64_16 { signed: bool },
+    I64_32 { signed: bool },
+}
+
+impl LoadKind {
+    /// The number of bytes read from memory.
+    pub fn width(&self) -> u32 {
+        match self {
+            LoadKind::I32_8 { .. } | LoadKind::I64_8 { .. } => 1,
+            LoadKind::I32_16 { .. } | LoadKind::I64_16 { .. } => 2,
+            LoadKind::I32 | LoadKind::F32 | LoadKind::I64_32 { .. } => 4,
+            LoadKind::I64 | LoadKind::F64 => 8,
+        }
+    }
+
+    /// The type of the loaded value.
+    pub fn result_type(&self) -> ValType {
+        match self {
+            LoadKind::I32 | LoadKind::I32_8 { .. } | LoadKind::I32_16 { .. } => ValType::I32,
+            LoadKind::I64
+            | LoadKind::I64_8 { .. }
+            | LoadKind::I64_16 { .. }
+            | LoadKind::I64_32 { .. } => ValType::I64,
+            LoadKind::F32 => ValType::F32,
+            LoadKind::F64 => ValType::F64,
+        }
+    }
+}
+
+impl fmt::Display for LoadKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let sign = |signed: bool| if signed { "_s" } else { "_u" };
+        match self {
+            LoadKind::I32 | LoadKind::I64 | LoadKind::F32 | LoadKind::F64 => {
+                write!(f, "{}.load", self.result_type())
+            }
+            LoadKind::I32_8 { signed }
+            | LoadKind::I32_16 { signed }
+            | LoadKind::I64_8 { signed }
+            | LoadKind::I64_16 { signed }
+            | LoadKind::I64_32 { signed } => write!(
+                f,
+                "{}.load{}{}",
+                self.result_type(),
+                self.width() * 8,
+                sign(*signed)
+            ),
+        }
+    }
+}
+
+/// The kinds of memory stores.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub enum StoreKind {
+    I32,
+    I64,
+    F32,
+    F64,
+    I32_8,
+    I32_16,
+    I64_8,
+    I64_16,
+    I64_32,
+}
+
+impl StoreKind {
+    /// The number of bytes written to memory.
+    pub fn width(&self) -> u32 {
+        match self {
+            StoreKind::I32_8 | StoreKind::I64_8 => 1,
+            StoreKind::I32_16 | StoreKind::I64_16 => 2,
+            StoreKind::I32 | StoreKind::F32 | StoreKind::I64_32 => 4,
+            StoreKind::I64 | StoreKind::F64 => 8,
+        }
+    }
+
+    /// The type of the stored value.
+    pub fn value_type(&self) -> ValType {
+        match self {
+            StoreKind::I32 | StoreKind::I32_8 | StoreKind::I32_16 => ValType::I32,
+            StoreKind::I64 | StoreKind::I64_8 | StoreKind::I64_16 | StoreKind::I64_32 => {
+                ValType::I64
+            }
+            StoreKind::F32 => ValType::F32,
+            StoreKind::F64 => ValType::F64,
+        }
+    }
+}
+
+impl fmt::Display for StoreKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StoreKind::I32 | StoreKind::I64 | StoreKind::F32 | StoreKind::F64 => {
+                write!(f, "{}.store", self.value_type())
+            }
+            _ => write!(f, "{}.store{}", self.value_type(), self.width() * 8),
+        }
+    }
+}
+
+/// The widths of atomic read-modify-write accesses.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub enum AtomicWidth {
+    I32,
+    I64,
+    I32_8,
+    I32_16,
+    I64_8,
+    I64_16,
+    I64_32,
+}
+
+impl AtomicWidth {
+    /// The number of bytes read from and written to memory.
+    pub fn width(&self) -> u32 {
+        match self {
+            AtomicWidth::I32_8 | AtomicWidth::I64_8 => 1,
+            AtomicWidth::I32_16 | AtomicWidth::I64_16 => 2,
+            AtomicWidth::I32 | AtomicWidth::I64_32 => 4,
+            AtomicWidth::I64 => 8,
+        }
+    }
+
+    /// The type of the operands and of the value that was in memory.
+    pub fn value_type(&self) -> ValType {
+        match self {
+            AtomicWidth::I32 | AtomicWidth::I32_8 | AtomicWidth::I32_16 => ValType::I32,
+            AtomicWidth::I64 | AtomicWidth::I64_8 | AtomicWidth::I64_16 | AtomicWidth::I64_32 => {
+                ValType::I64
+            }
+        }
+    }
+}
+
+impl fmt::Display for AtomicWidth {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AtomicWidth::I32 | AtomicWidth::I64 => write!(f, "{}.atomic.rmw", self.value_type()),
+            _ => write!(f, "{}.atomic.rmw{}_u", self.value_type(), self.width() * 8),
+        }
+    }
+}
+
+/// The operations of atomic read-modify-write accesses.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub enum AtomicOp {
+    Add,
+    Sub,
+    And,
+    Or,
+    Xor,
+    Xchg,
+}
+
+impl fmt::Display for AtomicOp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            AtomicOp::Add => "add",
+            AtomicOp::Sub => "sub",
+            AtomicOp::And => "and",
+            AtomicOp::Or => "or",
+            AtomicOp::Xor => "xor",
+            AtomicOp::Xchg => "xchg",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// The name of a load or store, with `.atomic` after its type if it is
+/// atomic: `i32.load8_u` becomes `i32.atomic.load8_u`.
+pub(crate) fn access_name(kind: &dyn fmt::Display, atomic: bool) -> String {
+    let name = kind.to_string();
+    if atomic {
+        name.replacen('.', ".atomic.", 1)
+    } else {
+        name
+    }
+}
+
+/// Write a memory access's immediates in the text format, leaving out the
+/// default offset and alignment.
+pub(crate) fn write_mem_arg(out: &mut dyn fmt::Write, arg: &MemArg, width: u32) -> fmt::Result {
+    if arg.offset != 0 {
+        write!(out, " offset={}", arg.offset)?;
+    }
+    if arg.align != width {
+        write!(out, " align={}", arg.align)?;
+    }
+    Ok(())
+}
+
 /// TODO
 #[derive(Debug, WalrusExpr)]
 pub enum Expr {
@@ -105,6 +454,24 @@ pub enum Expr {
         value: ExprId,
     },
 
+    /// `get_global n`
+    GetGlobal {
+        /// The type of this global.
+        ty: ValType,
+        /// The n^th global.
+        global: u32,
+    },
+
+    /// `set_global n`
+    SetGlobal {
+        /// The type of this global.
+        ty: ValType,
+        /// The n^th global, which is always mutable.
+        global: u32,
+        /// The value to set the global to.
+        value: ExprId,
+    },
+
     /// TODO
     I32Const(i32),
 
@@ -116,9 +483,24 @@ pub enum Expr {
     /// TODO
     I32Mul(ExprId, ExprId),
 
+    /// `i32.div_s`
+    I32DivS(ExprId, ExprId),
+    /// `i32.div_u`
+    I32DivU(ExprId, ExprId),
+    /// `i32.rem_s`
+    I32RemS(ExprId, ExprId),
+    /// `i32.rem_u`
+    I32RemU(ExprId, ExprId),
+
     /// `i32.eqz`
     I32Eqz(ExprId),
 
+    /// `i32.eq`
+    I32Eq(ExprId, ExprId),
+
+    /// `i32.and`
+    I32And(ExprId, ExprId),
+
     /// `i32.popcnt`
     I32Popcnt(ExprId),
 
@@ -134,6 +516,150 @@ pub enum Expr {
         alternative: ExprId,
     },
 
+    /// `<ty>[.atomic].load[N_s|N_u]`
+    Load {
+        /// The kind of load.
+        kind: LoadKind,
+        /// Is this an atomic load? Atomic loads are never sign-extending.
+        atomic: bool,
+        /// The alignment and offset of the access.
+        arg: MemArg,
+        /// The dynamic address to load from.
+        address: ExprId,
+    },
+
+    /// `<ty>[.atomic].store[N]`
+    Store {
+        /// The kind of store.
+        kind: StoreKind,
+        /// Is this an atomic store? Atomic stores are never of floats.
+        atomic: bool,
+        /// The alignment and offset of the access.
+        arg: MemArg,
+        /// The dynamic address to store to.
+        address: ExprId,
+        /// The value to store.
+        value: ExprId,
+    },
+
+    /// `<ty>.atomic.rmw[N_u].<op>`
+    AtomicRmw {
+        /// The operation combining the value in memory with `value`.
+        op: AtomicOp,
+        /// The width of the access.
+        width: AtomicWidth,
+        /// The alignment and offset of the access.
+        arg: MemArg,
+        /// The dynamic address to access.
+        address: ExprId,
+        /// The operand of the operation.
+        value: ExprId,
+    },
+
+    /// `<ty>.atomic.rmw[N_u].cmpxchg`
+    Cmpxchg {
+        /// The width of the access.
+        width: AtomicWidth,
+        /// The alignment and offset of the access.
+        arg: MemArg,
+        /// The dynamic address to access.
+        address: ExprId,
+        /// The value expected to be in memory.
+        expected: ExprId,
+        /// The value stored if the expected value was in memory.
+        replacement: ExprId,
+    },
+
+    /// `i32.atomic.wait` and `i64.atomic.wait`
+    AtomicWait {
+        /// Is the expected value an `i64` rather than an `i32`?
+        sixty_four: bool,
+        /// The alignment and offset of the access.
+        arg: MemArg,
+        /// The dynamic address to wait on.
+        address: ExprId,
+        /// The value expected to be in memory.
+        expected: ExprId,
+        /// The relative timeout in nanoseconds, or negative for none.
+        timeout: ExprId,
+    },
+
+    /// `atomic.notify`, also known as `atomic.wake`
+    AtomicNotify {
+        /// The alignment and offset of the access.
+        arg: MemArg,
+        /// The dynamic address to notify waiters of.
+        address: ExprId,
+        /// The maximum number of waiters to wake.
+        count: ExprId,
+    },
+
+    /// `<shape>.splat`
+    Splat {
+        /// The shape of the resulting vector.
+        shape: VecShape,
+        /// The value copied into every lane.
+        value: ExprId,
+    },
+
+    /// `<shape>.extract_lane[_s|_u] n`
+    ExtractLane {
+        /// The shape of the vector.
+        shape: VecShape,
+        /// Whether an `i8x16` or `i16x8` lane is sign extended (rather than
+        /// zero extended) to an `i32`. Always `false` for other shapes.
+        signed: bool,
+        /// The index of the lane to extract.
+        lane: u8,
+        /// The vector to extract the lane from.
+        vector: ExprId,
+    },
+
+    /// `<shape>.replace_lane n`
+    ReplaceLane {
+        /// The shape of the vector.
+        shape: VecShape,
+        /// The index of the lane to replace.
+        lane: u8,
+        /// The vector whose lane is replaced.
+        vector: ExprId,
+        /// The new value of the lane.
+        value: ExprId,
+    },
+
+    /// `<shape>.<op>`, e.g. `i32x4.add`
+    VecBinop {
+        /// The shape of both operands and the result.
+        shape: VecShape,
+        /// The lane-wise operation.
+        op: VecBinaryOp,
+        /// The left-hand side.
+        lhs: ExprId,
+        /// The right-hand side.
+        rhs: ExprId,
+    },
+
+    /// `<shape>.<op>`, e.g. `f32x4.neg`
+    VecUnop {
+        /// The shape of the operand and the result.
+        shape: VecShape,
+        /// The lane-wise operation.
+        op: VecUnaryOp,
+        /// The operand.
+        value: ExprId,
+    },
+
+    /// `v8x16.shuffle`
+    Shuffle {
+        /// For each lane of the result, the index of the lane to take from
+        /// the concatenation of `lhs` and `rhs`. Always less than 32.
+        lanes: [u8; 16],
+        /// The first 16 lanes to choose from.
+        lhs: ExprId,
+        /// The last 16 lanes to choose from.
+        rhs: ExprId,
+    },
+
     /// TODO
     Unreachable,
 
@@ -164,7 +690,9 @@ pub enum Expr {
         condition: ExprId,
         /// The block to execute when the condition is true.
         consequent: BlockId,
-        /// The block to execute when the condition is false.
+        /// The block to execute when the condition is false. An `if` without
+        /// an `else` still has an alternative block, which is empty and just
+        /// branches to the continuation.
         alternative: BlockId,
     },
 
@@ -203,6 +731,140 @@ impl Expr {
             _ => false,
         }
     }
+
+    /// Get the expressions whose values this expression uses as operands, in
+    /// the order they are pushed onto the stack.
+    pub fn operands(&self) -> Vec<ExprId> {
+        match self {
+            Expr::GetLocal { .. }
+            | Expr::GetGlobal { .. }
+            | Expr::I32Const(_)
+            | Expr::Unreachable
+            | Expr::Phi => vec![],
+            Expr::SetLocal { value, .. } | Expr::SetGlobal { value, .. } => vec![*value],
+            Expr::I32Add(lhs, rhs)
+            | Expr::I32Sub(lhs, rhs)
+            | Expr::I32Mul(lhs, rhs)
+            | Expr::I32DivS(lhs, rhs)
+            | Expr::I32DivU(lhs, rhs)
+            | Expr::I32RemS(lhs, rhs)
+            | Expr::I32RemU(lhs, rhs)
+            | Expr::I32Eq(lhs, rhs)
+            | Expr::I32And(lhs, rhs) => vec![*lhs, *rhs],
+            Expr::I32Eqz(e) | Expr::I32Popcnt(e) | Expr::Drop(e) => vec![*e],
+            Expr::Load { address, .. } => vec![*address],
+            Expr::Store { address, value, .. } | Expr::AtomicRmw { address, value, .. } => {
+                vec![*address, *value]
+            }
+            Expr::Cmpxchg {
+                address,
+                expected,
+                replacement,
+                ..
+            } => vec![*address, *expected, *replacement],
+            Expr::AtomicWait {
+                address,
+                expected,
+                timeout,
+                ..
+            } => vec![*address, *expected, *timeout],
+            Expr::AtomicNotify { address, count, .. } => vec![*address, *count],
+            Expr::Splat { value, .. } => vec![*value],
+            Expr::ExtractLane { vector, .. } => vec![*vector],
+            Expr::VecBinop { lhs, rhs, .. } | Expr::Shuffle { lhs, rhs, .. } => {
+                vec![*lhs, *rhs]
+            }
+            Expr::VecUnop { value, .. } => vec![*value],
+            Expr::ReplaceLane { vector, value, .. } => vec![*vector, *value],
+            Expr::Select {
+                condition,
+                consequent,
+                alternative,
+            } => vec![*alternative, *consequent, *condition],
+            Expr::Br { args, .. } => args.to_vec(),
+            Expr::BrIf {
+                condition, args, ..
+            } => {
+                let mut operands = args.to_vec();
+                operands.push(*condition);
+                operands
+            }
+            Expr::IfElse { condition, .. } => vec![*condition],
+            Expr::BrTable { which, args, .. } => {
+                let mut operands = args.to_vec();
+                operands.push(*which);
+                operands
+            }
+            Expr::Return { values } => values.to_vec(),
+        }
+    }
+
+    /// Get mutable references to this expression's operands, in the same
+    /// order as `operands`.
+    pub fn operands_mut(&mut self) -> Vec<&mut ExprId> {
+        match self {
+            Expr::GetLocal { .. }
+            | Expr::GetGlobal { .. }
+            | Expr::I32Const(_)
+            | Expr::Unreachable
+            | Expr::Phi => vec![],
+            Expr::SetLocal { value, .. } | Expr::SetGlobal { value, .. } => vec![value],
+            Expr::I32Add(lhs, rhs)
+            | Expr::I32Sub(lhs, rhs)
+            | Expr::I32Mul(lhs, rhs)
+            | Expr::I32DivS(lhs, rhs)
+            | Expr::I32DivU(lhs, rhs)
+            | Expr::I32RemS(lhs, rhs)
+            | Expr::I32RemU(lhs, rhs)
+            | Expr::I32Eq(lhs, rhs)
+            | Expr::I32And(lhs, rhs) => vec![lhs, rhs],
+            Expr::I32Eqz(e) | Expr::I32Popcnt(e) | Expr::Drop(e) => vec![e],
+            Expr::Load { address, .. } => vec![address],
+            Expr::Store { address, value, .. } | Expr::AtomicRmw { address, value, .. } => {
+                vec![address, value]
+            }
+            Expr::Cmpxchg {
+                address,
+                expected,
+                replacement,
+                ..
+            } => vec![address, expected, replacement],
+            Expr::AtomicWait {
+                address,
+                expected,
+                timeout,
+                ..
+            } => vec![address, expected, timeout],
+            Expr::AtomicNotify { address, count, .. } => vec![address, count],
+            Expr::Splat { value, .. } => vec![value],
+            Expr::ExtractLane { vector, .. } => vec![vector],
+            Expr::VecBinop { lhs, rhs, .. } | Expr::Shuffle { lhs, rhs, .. } => {
+                vec![lhs, rhs]
+            }
+            Expr::VecUnop { value, .. } => vec![value],
+            Expr::ReplaceLane { vector, value, .. } => vec![vector, value],
+            Expr::Select {
+                condition,
+                consequent,
+                alternative,
+            } => vec![alternative, consequent, condition],
+            Expr::Br { args, .. } => args.iter_mut().collect(),
+            Expr::BrIf {
+                condition, args, ..
+            } => {
+                let mut operands: Vec<_> = args.iter_mut().collect();
+                operands.push(condition);
+                operands
+            }
+            Expr::IfElse { condition, .. } => vec![condition],
+            Expr::BrTable { which, args, .. } => {
+                let mut operands: Vec<_> = args.iter_mut().collect();
+                operands.push(which);
+                operands
+            }
+            Expr::Return { values } => values.iter_mut().collect(),
+        }
+    }
 }
 
 impl<'a> Dot for (ExprId, &'a Expr) {
@@ -231,6 +893,11 @@ impl<'a> Dot for (ExprId, &'a Expr) {
                 edge(&mut edges, &self.0, value, "value");
                 write!(out, "set_local {} ;; ty = {}", local, ty)?;
             }
+            Expr::GetGlobal { ty, global } => write!(out, "get_global {} ;; ty = {}", global, ty)?,
+            Expr::SetGlobal { ty, global, value } => {
+                edge(&mut edges, &self.0, value, "value");
+                write!(out, "set_global {} ;; ty = {}", global, ty)?;
+            }
             Expr::I32Const(n) => write!(out, "i32.const {}", n)?,
             Expr::I32Add(lhs, rhs) => {
                 edge(&mut edges, &self.0, lhs, "lhs");
@@ -247,10 +914,40 @@ impl<'a> Dot for (ExprId, &'a Expr) {
                 edge(&mut edges, &self.0, rhs, "rhs");
                 write!(out, "i32.mul")?;
             }
+            Expr::I32DivS(lhs, rhs) => {
+                edge(&mut edges, &self.0, lhs, "lhs");
+                edge(&mut edges, &self.0, rhs, "rhs");
+                write!(out, "i32.div_s")?;
+            }
+            Expr::I32DivU(lhs, rhs) => {
+                edge(&mut edges, &self.0, lhs, "lhs");
+                edge(&mut edges, &self.0, rhs, "rhs");
+                write!(out, "i32.div_u")?;
+            }
+            Expr::I32RemS(lhs, rhs) => {
+                edge(&mut edges, &self.0, lhs, "lhs");
+                edge(&mut edges, &self.0, rhs, "rhs");
+                write!(out, "i32.rem_s")?;
+            }
+            Expr::I32RemU(lhs, rhs) => {
+                edge(&mut edges, &self.0, lhs, "lhs");
+                edge(&mut edges, &self.0, rhs, "rhs");
+                write!(out, "i32.rem_u")?;
+            }
             Expr::I32Eqz(e) => {
                 edge(&mut edges, &self.0, e, "value");
                 write!(out, "i32.eqz")?;
             }
+            Expr::I32Eq(lhs, rhs) => {
+                edge(&mut edges, &self.0, lhs, "lhs");
+                edge(&mut edges, &self.0, rhs, "rhs");
+                write!(out, "i32.eq")?;
+            }
+            Expr::I32And(lhs, rhs) => {
+                edge(&mut edges, &self.0, lhs, "lhs");
+                edge(&mut edges, &self.0, rhs, "rhs");
+                write!(out, "i32.and")?;
+            }
             Expr::I32Popcnt(e) => {
                 edge(&mut edges, &self.0, e, "value");
                 write!(out, "i32.popcnt")?;
@@ -260,11 +957,139 @@ impl<'a> Dot for (ExprId, &'a Expr) {
                 consequent,
                 alternative,
             } => {
-                edge(&mut edges, &self.0, condition, "condition");
-                edge(&mut edges, &self.0, consequent, "consequent");
                 edge(&mut edges, &self.0, alternative, "alternative");
+                edge(&mut edges, &self.0, consequent, "consequent");
+                edge(&mut edges, &self.0, condition, "condition");
                 write!(out, "select")?;
             }
+            Expr::Load {
+                kind,
+                atomic,
+                arg,
+                address,
+            } => {
+                edge(&mut edges, &self.0, address, "address");
+                let mut s = access_name(kind, *atomic);
+                write_mem_arg(&mut s, arg, kind.width()).unwrap();
+                write!(out, "{}", s)?;
+            }
+            Expr::Store {
+                kind,
+                atomic,
+                arg,
+                address,
+                value,
+            } => {
+                edge(&mut edges, &self.0, address, "address");
+                edge(&mut edges, &self.0, value, "value");
+                let mut s = access_name(kind, *atomic);
+                write_mem_arg(&mut s, arg, kind.width()).unwrap();
+                write!(out, "{}", s)?;
+            }
+            Expr::AtomicRmw {
+                op,
+                width,
+                arg,
+                address,
+                value,
+            } => {
+                edge(&mut edges, &self.0, address, "address");
+                edge(&mut edges, &self.0, value, "value");
+                let mut s = format!("{}.{}", width, op);
+                write_mem_arg(&mut s, arg, width.width()).unwrap();
+                write!(out, "{}", s)?;
+            }
+            Expr::Cmpxchg {
+                width,
+                arg,
+                address,
+                expected,
+                replacement,
+            } => {
+                edge(&mut edges, &self.0, address, "address");
+                edge(&mut edges, &self.0, expected, "expected");
+                edge(&mut edges, &self.0, replacement, "replacement");
+                let mut s = format!("{}.cmpxchg", width);
+                write_mem_arg(&mut s, arg, width.width()).unwrap();
+                write!(out, "{}", s)?;
+            }
+            Expr::AtomicWait {
+                sixty_four,
+                arg,
+                address,
+                expected,
+                timeout,
+            } => {
+                edge(&mut edges, &self.0, address, "address");
+                edge(&mut edges, &self.0, expected, "expected");
+                edge(&mut edges, &self.0, timeout, "timeout");
+                let (ty, width) = if *sixty_four { ("i64", 8) } else { ("i32", 4) };
+                let mut s = format!("{}.atomic.wait", ty);
+                write_mem_arg(&mut s, arg, width).unwrap();
+                write!(out, "{}", s)?;
+            }
+            Expr::AtomicNotify {
+                arg,
+                address,
+                count,
+            } => {
+                edge(&mut edges, &self.0, address, "address");
+                edge(&mut edges, &self.0, count, "count");
+                let mut s = "atomic.notify".to_string();
+                write_mem_arg(&mut s, arg, 4).unwrap();
+                write!(out, "{}", s)?;
+            }
+            Expr::Splat { shape, value } => {
+                edge(&mut edges, &self.0, value, "value");
+                write!(out, "{}.splat", shape)?;
+            }
+            Expr::ExtractLane {
+                shape,
+                signed,
+                lane,
+                vector,
+            } => {
+                edge(&mut edges, &self.0, vector, "vector");
+                write!(
+                    out,
+                    "{}.extract_lane{} {}",
+                    shape,
+                    extract_lane_suffix(*shape, *signed),
+                    lane
+                )?;
+            }
+            Expr::ReplaceLane {
+                shape,
+                lane,
+                vector,
+                value,
+            } => {
+                edge(&mut edges, &self.0, vector, "vector");
+                edge(&mut edges, &self.0, value, "value");
+                write!(out, "{}.replace_lane {}", shape, lane)?;
+            }
+            Expr::VecBinop {
+                shape,
+                op,
+                lhs,
+                rhs,
+            } => {
+                edge(&mut edges, &self.0, lhs, "lhs");
+                edge(&mut edges, &self.0, rhs, "rhs");
+                write!(out, "{}.{}", shape, op)?;
+            }
+            Expr::VecUnop { shape, op, value } => {
+                edge(&mut edges, &self.0, value, "value");
+                write!(out, "{}.{}", shape, op)?;
+            }
+            Expr::Shuffle { lanes, lhs, rhs } => {
+                edge(&mut edges, &self.0, lhs, "lhs");
+                edge(&mut edges, &self.0, rhs, "rhs");
+                write!(out, "v8x16.shuffle")?;
+                for lane in lanes.iter() {
+                    write!(out, " {}", lane)?;
+                }
+            }
 
             Expr::Unreachable => write!(out, "unreachable")?,
             Expr::Phi => write!(out, "phi")?,
@@ -347,3 +1172,13 @@ impl<'a> Dot for (ExprId, &'a Expr) {
         Ok(())
     }
 }
+
+/// The `_s`/`_u` suffix of an `extract_lane` instruction, which only the
+/// shapes with lanes narrower than 32 bits have.
+pub(crate) fn extract_lane_suffix(shape: VecShape, signed: bool) -> &'static str {
+    match (shape, signed) {
+        (VecShape::I8x16, true) | (VecShape::I16x8, true) => "_s",
+        (VecShape::I8x16, false) | (VecShape::I16x8, false) => "_u",
+        _ => "",
+    }
+}
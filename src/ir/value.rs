@@ -0,0 +1,395 @@
+//! Constant values of every value type, and their notation in the text
+//! format.
+
+use super::VecShape;
+use crate::error::{ErrorKind, Result};
+use crate::ValType;
+use failure::Fail;
+use std::fmt;
+
+/// A constant value of any value type.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Value {
+    /// An `i32` value.
+    I32(i32),
+    /// An `i64` value.
+    I64(i64),
+    /// An `f32` value.
+    F32(f32),
+    /// An `f64` value.
+    F64(f64),
+    /// A `v128` value. Its least significant byte is lane 0 when it is split
+    /// into `i8x16` lanes.
+    V128(u128),
+}
+
+impl Value {
+    /// The type of this value.
+    pub fn ty(&self) -> ValType {
+        match self {
+            Value::I32(_) => ValType::I32,
+            Value::I64(_) => ValType::I64,
+            Value::F32(_) => ValType::F32,
+            Value::F64(_) => ValType::F64,
+            Value::V128(_) => ValType::V128,
+        }
+    }
+
+    /// Parse a value of the given type, written the way it would be after
+    /// `i32.const` and friends in the text format. This is the inverse of
+    /// `Value`'s `Display` implementation.
+    ///
+    /// Integers may be written in decimal or hex, and in either their signed
+    /// or unsigned range. Floats may be written in decimal or as hex floats,
+    /// or be `inf`, `nan`, or `nan:0x` followed by a payload. A `v128` is a
+    /// shape, such as `i32x4`, followed by each of its lanes.
+    pub fn parse(s: &str, ty: ValType) -> Result<Value> {
+        let value = match ty {
+            ValType::I32 => parse_int(s, 32).map(|n| Value::I32(n as i32)),
+            ValType::I64 => parse_int(s, 64).map(|n| Value::I64(n as i64)),
+            ValType::F32 => parse_float(s, &F32).map(|n| Value::F32(f32::from_bits(n as u32))),
+            ValType::F64 => parse_float(s, &F64).map(|n| Value::F64(f64::from_bits(n))),
+            ValType::V128 => parse_v128(s).map(Value::V128),
+        };
+        value.ok_or_else(|| {
+            ErrorKind::InvalidValue
+                .context(format!("`{}` is not a valid {}", s, ty))
+                .into()
+        })
+    }
+}
+
+/// Integers are displayed in decimal, and floats as exact hex floats, or as
+/// `inf` or `nan`, with `nan:0x` and the payload for anything but the
+/// canonical NaN. A `v128` is displayed as its sixteen `i8x16` lanes in hex.
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Value::I32(n) => write!(f, "{}", n),
+            Value::I64(n) => write!(f, "{}", n),
+            Value::F32(x) => display_float(f, u64::from(x.to_bits()), &F32),
+            Value::F64(x) => display_float(f, x.to_bits(), &F64),
+            Value::V128(n) => {
+                write!(f, "{}", VecShape::I8x16)?;
+                for i in 0..16 {
+                    write!(f, " 0x{:02x}", (n >> (i * 8)) as u8)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// The layout of an IEEE 754 binary float.
+struct FloatFormat {
+    mantissa_bits: u32,
+    exponent_bits: u32,
+    /// Parse an unsigned decimal float, returning its bits, or `None` if it
+    /// is not finite in this format.
+    parse_decimal: fn(&str) -> Option<u64>,
+}
+
+const F32: FloatFormat = FloatFormat {
+    mantissa_bits: 23,
+    exponent_bits: 8,
+    parse_decimal: |s| {
+        s.parse::<f32>()
+            .ok()
+            .filter(|x| x.is_finite())
+            .map(|x| u64::from(x.to_bits()))
+    },
+};
+
+const F64: FloatFormat = FloatFormat {
+    mantissa_bits: 52,
+    exponent_bits: 11,
+    parse_decimal: |s| {
+        s.parse::<f64>()
+            .ok()
+            .filter(|x| x.is_finite())
+            .map(|x| x.to_bits())
+    },
+};
+
+impl FloatFormat {
+    fn bias(&self) -> i64 {
+        (1 << (self.exponent_bits - 1)) - 1
+    }
+
+    /// The exponent field of infinities and NaNs.
+    fn max_exponent(&self) -> u64 {
+        (1 << self.exponent_bits) - 1
+    }
+
+    fn mantissa_mask(&self) -> u64 {
+        (1 << self.mantissa_bits) - 1
+    }
+}
+
+fn display_float(f: &mut fmt::Formatter, bits: u64, format: &FloatFormat) -> fmt::Result {
+    let m = format.mantissa_bits;
+    let exponent = (bits >> m) & format.max_exponent();
+    let mantissa = bits & format.mantissa_mask();
+    if (bits >> (m + format.exponent_bits)) & 1 == 1 {
+        write!(f, "-")?;
+    }
+
+    if exponent == format.max_exponent() {
+        return if mantissa == 0 {
+            write!(f, "inf")
+        } else if mantissa == 1 << (m - 1) {
+            write!(f, "nan")
+        } else {
+            write!(f, "nan:0x{:x}", mantissa)
+        };
+    }
+    if exponent == 0 && mantissa == 0 {
+        return write!(f, "0x0p+0");
+    }
+
+    // Subnormals have an implicit leading 0 and the smallest normal exponent.
+    let (leading, exponent) = if exponent == 0 {
+        (0, 1 - format.bias())
+    } else {
+        (1, exponent as i64 - format.bias())
+    };
+    // Pad the mantissa out to a whole number of hex digits.
+    let pad = (4 - m % 4) % 4;
+    let digits = format!(
+        "{:0width$x}",
+        mantissa << pad,
+        width = ((m + pad) / 4) as usize
+    );
+    let digits = digits.trim_end_matches('0');
+    write!(f, "0x{}", leading)?;
+    if !digits.is_empty() {
+        write!(f, ".{}", digits)?;
+    }
+    write!(f, "p{:+}", exponent)
+}
+
+/// Split an optional sign off the front of the given text, returning whether
+/// it was negative.
+fn split_sign(s: &str) -> (bool, &str) {
+    if let Some(s) = s.strip_prefix('-') {
+        (true, s)
+    } else {
+        (false, s.strip_prefix('+').unwrap_or(s))
+    }
+}
+
+/// Check that the given text is a non-empty run of digits in the given radix,
+/// optionally separated by single underscores, and return it without them.
+fn digits(s: &str, radix: u32) -> Option<String> {
+    if s.is_empty() || s.starts_with('_') || s.ends_with('_') || s.contains("__") {
+        return None;
+    }
+    if !s.chars().all(|c| c == '_' || c.is_digit(radix)) {
+        return None;
+    }
+    Some(s.chars().filter(|c| *c != '_').collect())
+}
+
+/// Parse an integer that fits in `bits` bits as either a signed or an
+/// unsigned number, returning those bits.
+fn parse_int(s: &str, bits: u32) -> Option<u64> {
+    let (negative, s) = split_sign(s);
+    let magnitude = if let Some(hex) = s.strip_prefix("0x") {
+        u128::from_str_radix(&digits(hex, 16)?, 16).ok()?
+    } else {
+        digits(s, 10)?.parse::<u128>().ok()?
+    };
+
+    let max = if negative {
+        1 << (bits - 1)
+    } else {
+        (1 << bits) - 1
+    };
+    if magnitude > max {
+        return None;
+    }
+    let n = magnitude as u64;
+    let n = if negative { n.wrapping_neg() } else { n };
+    Some(n & (!0 >> (64 - bits)))
+}
+
+/// Parse a float in the given format, returning its bits.
+fn parse_float(s: &str, format: &FloatFormat) -> Option<u64> {
+    let (negative, s) = split_sign(s);
+    let sign = u64::from(negative) << (format.mantissa_bits + format.exponent_bits);
+    let infinity = format.max_exponent() << format.mantissa_bits;
+
+    let magnitude = if s == "inf" {
+        infinity
+    } else if s == "nan" {
+        infinity | 1 << (format.mantissa_bits - 1)
+    } else if let Some(payload) = s.strip_prefix("nan:0x") {
+        let payload = u64::from_str_radix(&digits(payload, 16)?, 16).ok()?;
+        if payload == 0 || payload > format.mantissa_mask() {
+            return None;
+        }
+        infinity | payload
+    } else if let Some(hex) = s.strip_prefix("0x") {
+        parse_hex_float(hex, format)?
+    } else {
+        parse_decimal_float(s, format)?
+    };
+    Some(sign | magnitude)
+}
+
+/// Parse an unsigned decimal float, such as `1.5e-3`.
+fn parse_decimal_float(s: &str, format: &FloatFormat) -> Option<u64> {
+    let (mantissa, exponent) = match s.find(['e', 'E']) {
+        Some(i) => (&s[..i], Some(&s[i + 1..])),
+        None => (s, None),
+    };
+    let (int, frac) = match mantissa.find('.') {
+        Some(i) => (&mantissa[..i], &mantissa[i + 1..]),
+        None => (mantissa, ""),
+    };
+
+    let mut text = digits(int, 10)?;
+    if !frac.is_empty() {
+        text.push('.');
+        text.push_str(&digits(frac, 10)?);
+    }
+    if let Some(exponent) = exponent {
+        let (negative, exponent) = split_sign(exponent);
+        text.push_str(if negative { "e-" } else { "e" });
+        text.push_str(&digits(exponent, 10)?);
+    }
+    (format.parse_decimal)(&text)
+}
+
+/// Parse an unsigned hex float without its `0x`, such as `1.8p+3`, rounding
+/// it to the nearest float in the given format.
+fn parse_hex_float(s: &str, format: &FloatFormat) -> Option<u64> {
+    let (mantissa, exponent) = match s.find(['p', 'P']) {
+        Some(i) => (&s[..i], Some(&s[i + 1..])),
+        None => (s, None),
+    };
+    let (int, frac) = match mantissa.find('.') {
+        Some(i) => (&mantissa[..i], &mantissa[i + 1..]),
+        None => (mantissa, ""),
+    };
+    let int = digits(int, 16)?;
+    let frac = if frac.is_empty() {
+        String::new()
+    } else {
+        digits(frac, 16)?
+    };
+    let mut exponent = match exponent {
+        Some(exponent) => {
+            let (negative, exponent) = split_sign(exponent);
+            // Anything this far out of range overflows or underflows anyway.
+            let exponent = digits(exponent, 10)?
+                .parse::<i64>()
+                .unwrap_or(i64::MAX)
+                .min(1 << 20);
+            if negative {
+                -exponent
+            } else {
+                exponent
+            }
+        }
+        None => 0,
+    };
+
+    // Accumulate the digits into `mantissa`, so the value is
+    // `mantissa * 2^exponent`. Digits that do not fit can only matter for
+    // rounding, so they are folded into the lowest bit.
+    let mut mantissa: u128 = 0;
+    let int = int.chars().map(|c| (c, false));
+    let frac = frac.chars().map(|c| (c, true));
+    for (c, fractional) in int.chain(frac) {
+        let digit = u128::from(c.to_digit(16).unwrap());
+        if mantissa >> 120 == 0 {
+            mantissa = mantissa << 4 | digit;
+            if fractional {
+                exponent -= 4;
+            }
+        } else {
+            mantissa |= (digit != 0) as u128;
+            if !fractional {
+                exponent += 4;
+            }
+        }
+    }
+    round_float(mantissa, exponent, format)
+}
+
+/// Round `mantissa * 2^exponent` to the nearest float in the given format,
+/// with ties to even, returning its bits, or `None` if it overflows.
+fn round_float(mantissa: u128, exponent: i64, format: &FloatFormat) -> Option<u64> {
+    if mantissa == 0 {
+        return Some(0);
+    }
+    let m = i64::from(format.mantissa_bits);
+
+    // The value is in `[2^top, 2^(top + 1))`, and is rounded to a multiple of
+    // `2^(result - m)`.
+    let top = 127 - i64::from(mantissa.leading_zeros()) + exponent;
+    let mut result = top.max(1 - format.bias());
+    let shift = result - m - exponent;
+    let mut rounded = if shift <= 0 {
+        mantissa << -shift
+    } else if shift >= 128 {
+        0
+    } else {
+        let truncated = mantissa >> shift;
+        let rest = mantissa & ((1 << shift) - 1);
+        let half = 1 << (shift - 1);
+        if rest > half || (rest == half && truncated & 1 == 1) {
+            truncated + 1
+        } else {
+            truncated
+        }
+    };
+    if rounded >> (m + 1) != 0 {
+        rounded >>= 1;
+        result += 1;
+    }
+
+    // Without the implicit leading 1, this is a subnormal.
+    let biased = if rounded >> m == 0 {
+        0
+    } else {
+        result + format.bias()
+    };
+    if biased >= format.max_exponent() as i64 {
+        return None;
+    }
+    Some((biased as u64) << m | (rounded as u64 & format.mantissa_mask()))
+}
+
+/// Parse a `v128` written as a shape followed by its lanes, such as
+/// `i32x4 1 2 3 4`.
+fn parse_v128(s: &str) -> Option<u128> {
+    let mut parts = s.split_whitespace();
+    let shape = match parts.next()? {
+        "i8x16" => VecShape::I8x16,
+        "i16x8" => VecShape::I16x8,
+        "i32x4" => VecShape::I32x4,
+        "i64x2" => VecShape::I64x2,
+        "f32x4" => VecShape::F32x4,
+        "f64x2" => VecShape::F64x2,
+        _ => return None,
+    };
+    let bits = 128 / u32::from(shape.lanes());
+    let lanes = parts
+        .map(|lane| match shape {
+            VecShape::F32x4 => parse_float(lane, &F32),
+            VecShape::F64x2 => parse_float(lane, &F64),
+            _ => parse_int(lane, bits),
+        })
+        .collect::<Option<Vec<_>>>()?;
+    if lanes.len() != usize::from(shape.lanes()) {
+        return None;
+    }
+    Some(
+        lanes
+            .iter()
+            .enumerate()
+            .fold(0, |n, (i, lane)| n | u128::from(*lane) << (i as u32 * bits)),
+    )
+}
@@ -3,11 +3,13 @@
 #![deny(missing_debug_implementations)]
 #![deny(missing_docs)]
 
+pub mod analysis;
 pub mod chunk_list;
 pub mod dot;
 pub mod error;
 pub mod function;
 pub mod ir;
+pub mod passes;
 pub mod validation_context;
 
 use failure::Fail;
@@ -41,6 +43,18 @@ impl<'a> From<&'a elements::ValueType> for ValType {
     }
 }
 
+impl From<ValType> for elements::ValueType {
+    fn from(x: ValType) -> elements::ValueType {
+        match x {
+            ValType::I32 => elements::ValueType::I32,
+            ValType::I64 => elements::ValueType::I64,
+            ValType::F32 => elements::ValueType::F32,
+            ValType::F64 => elements::ValueType::F64,
+            ValType::V128 => elements::ValueType::V128,
+        }
+    }
+}
+
 impl ValType {
     fn from_block_ty(block_ty: &elements::BlockType) -> Vec<ValType> {
         match block_ty {
@@ -0,0 +1,22 @@
+//! Remove dead code following unconditional jumps.
+//!
+//! Once a block has branched, returned, or hit an `unreachable`, nothing after
+//! that point in the block is ever executed, so it is truncated away. Anything
+//! before the jump is kept, whether it has side effects or not.
+
+use crate::function::Function;
+use crate::ir::BlockId;
+
+/// Run this pass over the given function.
+pub fn run(func: &mut Function) {
+    let blocks: Vec<BlockId> = func.blocks.iter().map(|(id, _)| id).collect();
+    for block in blocks {
+        let first_jump = func.blocks[block]
+            .exprs
+            .iter()
+            .position(|e| func.exprs[*e].is_jump());
+        if let Some(idx) = first_jump {
+            func.blocks[block].exprs.truncate(idx + 1);
+        }
+    }
+}
@@ -0,0 +1,63 @@
+//! Fold arithmetic on constants into a single constant.
+//!
+//! `(i32.add (i32.const 2) (i32.const 3))` becomes `(i32.const 5)`, following
+//! wasm's wrapping semantics. Operations that would trap, like division by
+//! zero, are left alone so that they still trap at runtime.
+
+use crate::function::Function;
+use crate::ir::{Expr, ExprId};
+
+/// Run this pass over the given function.
+pub fn run(func: &mut Function) {
+    // Operands are always allocated before the expressions that use them, so
+    // visiting expressions in allocation order folds nested constant
+    // subexpressions before their parents.
+    let exprs: Vec<ExprId> = func.exprs.iter().map(|(id, _)| id).collect();
+    for id in exprs {
+        if let Some(value) = fold(func, &func.exprs[id]) {
+            // Replace the expression in place, so that every parent sharing
+            // it sees the constant.
+            func.exprs[id] = Expr::I32Const(value);
+        }
+    }
+}
+
+fn constant(func: &Function, expr: ExprId) -> Option<i32> {
+    match func.exprs[expr] {
+        Expr::I32Const(n) => Some(n),
+        _ => None,
+    }
+}
+
+fn fold(func: &Function, expr: &Expr) -> Option<i32> {
+    let binop = |lhs: ExprId, rhs: ExprId, f: &dyn Fn(i32, i32) -> Option<i32>| {
+        f(constant(func, lhs)?, constant(func, rhs)?)
+    };
+    match *expr {
+        Expr::I32Add(lhs, rhs) => binop(lhs, rhs, &|a, b| Some(a.wrapping_add(b))),
+        Expr::I32Sub(lhs, rhs) => binop(lhs, rhs, &|a, b| Some(a.wrapping_sub(b))),
+        Expr::I32Mul(lhs, rhs) => binop(lhs, rhs, &|a, b| Some(a.wrapping_mul(b))),
+        // `checked_div` is `None` for both trapping cases: a zero divisor and
+        // `i32::MIN / -1`.
+        Expr::I32DivS(lhs, rhs) => binop(lhs, rhs, &|a, b| a.checked_div(b)),
+        Expr::I32DivU(lhs, rhs) => binop(lhs, rhs, &|a, b| {
+            (a as u32).checked_div(b as u32).map(|n| n as i32)
+        }),
+        // Unlike division, `i32::MIN % -1` does not trap; it is zero.
+        Expr::I32RemS(lhs, rhs) => binop(lhs, rhs, &|a, b| {
+            if b == 0 {
+                None
+            } else {
+                Some(a.wrapping_rem(b))
+            }
+        }),
+        Expr::I32RemU(lhs, rhs) => binop(lhs, rhs, &|a, b| {
+            (a as u32).checked_rem(b as u32).map(|n| n as i32)
+        }),
+        Expr::I32Eq(lhs, rhs) => binop(lhs, rhs, &|a, b| Some((a == b) as i32)),
+        Expr::I32And(lhs, rhs) => binop(lhs, rhs, &|a, b| Some(a & b)),
+        Expr::I32Eqz(e) => constant(func, e).map(|n| (n == 0) as i32),
+        Expr::I32Popcnt(e) => constant(func, e).map(|n| n.count_ones() as i32),
+        _ => None,
+    }
+}
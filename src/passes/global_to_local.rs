@@ -0,0 +1,50 @@
+//! Read a global that is read several times, but never set, into a local
+//! once.
+//!
+//! The global is read into a new local at the start of the function's entry
+//! block, and every `get_global` of it becomes a `get_local` of that local.
+//! A global that the function sets, anywhere, is left alone, since a later
+//! read could see a different value. Immutable globals can never be set, so
+//! they are always hoisted when read more than once.
+//!
+//! The IR does not have calls yet, so nothing but a `set_global` in the
+//! function itself can change a global while it runs. Once it does, a call
+//! has to be treated like a `set_global` of every mutable global.
+
+use crate::function::Function;
+use crate::ir::{Expr, ExprId};
+use crate::ValType;
+use std::collections::{BTreeMap, HashSet};
+
+/// Run this pass over the given function.
+pub fn run(func: &mut Function) {
+    let mut reads: BTreeMap<u32, (ValType, Vec<ExprId>)> = BTreeMap::new();
+    let mut set = HashSet::new();
+    for id in func.used_exprs() {
+        match func.exprs[id] {
+            Expr::GetGlobal { ty, global } => {
+                reads.entry(global).or_insert((ty, vec![])).1.push(id);
+            }
+            Expr::SetGlobal { global, .. } => {
+                set.insert(global);
+            }
+            _ => {}
+        }
+    }
+
+    let mut prologue = vec![];
+    for (global, (ty, ids)) in reads {
+        if ids.len() < 2 || set.contains(&global) {
+            continue;
+        }
+        let local = func.add_local(ty);
+        for id in ids {
+            func.exprs[id] = Expr::GetLocal { ty, local };
+        }
+        let value = func.exprs.alloc(Expr::GetGlobal { ty, global });
+        prologue.push(func.exprs.alloc(Expr::SetLocal { ty, local, value }));
+    }
+
+    let entry = func.entry_block();
+    func.blocks[entry].exprs.splice(0..0, prologue);
+}
@@ -0,0 +1,9 @@
+//! Transformation passes over a function's IR.
+
+pub mod dce;
+pub mod fold_consts;
+pub mod global_to_local;
+pub mod peephole;
+pub mod remove_dead_drops;
+pub mod simplify_blocks;
+pub mod switch_gen;
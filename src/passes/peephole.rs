@@ -0,0 +1,103 @@
+//! Simplify expressions using algebraic identities.
+//!
+//! The identities applied are:
+//!
+//! * `x + 0` and `0 + x` become `x`
+//! * `x - 0` becomes `x`
+//! * `x * 1` and `1 * x` become `x`
+//! * `x * 0` and `0 * x` become `0`, but only when `x` has no side effects
+//! * `x & -1` and `-1 & x` become `x`
+//! * `(i32.eqz (i32.eqz (i32.eqz x)))` becomes `(i32.eqz x)`
+//!
+//! Simplified expressions are replaced by one of their operands in every
+//! parent that uses them, rather than rewritten in place, so that an operand
+//! with side effects is still only evaluated once.
+
+use crate::function::Function;
+use crate::ir::matcher::{
+    any, i32_const, I32AddMatcher, I32AndMatcher, I32EqzMatcher, I32MulMatcher, I32SubMatcher,
+    Matcher,
+};
+use crate::ir::{BlockId, Expr, ExprId};
+use std::collections::HashMap;
+
+/// Run this pass over the given function.
+pub fn run(func: &mut Function) {
+    let add_zero = I32AddMatcher::new(any(), i32_const(0));
+    let zero_add = I32AddMatcher::new(i32_const(0), any());
+    let sub_zero = I32SubMatcher::new(any(), i32_const(0));
+    let mul_one = I32MulMatcher::new(any(), i32_const(1));
+    let one_mul = I32MulMatcher::new(i32_const(1), any());
+    let mul_zero = I32MulMatcher::new(any(), i32_const(0));
+    let zero_mul = I32MulMatcher::new(i32_const(0), any());
+    let and_ones = I32AndMatcher::new(any(), i32_const(-1));
+    let ones_and = I32AndMatcher::new(i32_const(-1), any());
+    let triple_eqz = I32EqzMatcher::new(I32EqzMatcher::new(I32EqzMatcher::new(any())));
+
+    // Map from simplified expressions to the operand that replaces them.
+    let mut replacements = HashMap::new();
+    let exprs: Vec<ExprId> = func.exprs.iter().map(|(id, _)| id).collect();
+    for id in exprs {
+        let expr = &func.exprs[id];
+        let (lhs, rhs) = match *expr {
+            Expr::I32Add(lhs, rhs)
+            | Expr::I32Sub(lhs, rhs)
+            | Expr::I32Mul(lhs, rhs)
+            | Expr::I32And(lhs, rhs) => (lhs, rhs),
+            Expr::I32Eqz(inner) => {
+                if triple_eqz.is_match(func, expr) {
+                    replacements.insert(id, inner_operand(func, inner));
+                }
+                continue;
+            }
+            _ => continue,
+        };
+
+        if add_zero.is_match(func, expr)
+            || sub_zero.is_match(func, expr)
+            || mul_one.is_match(func, expr)
+            || and_ones.is_match(func, expr)
+        {
+            replacements.insert(id, lhs);
+        } else if zero_add.is_match(func, expr)
+            || one_mul.is_match(func, expr)
+            || ones_and.is_match(func, expr)
+        {
+            replacements.insert(id, rhs);
+        } else if (mul_zero.is_match(func, expr) && !func.has_side_effects(lhs))
+            || (zero_mul.is_match(func, expr) && !func.has_side_effects(rhs))
+        {
+            func.exprs[id] = Expr::I32Const(0);
+        }
+    }
+    if replacements.is_empty() {
+        return;
+    }
+
+    let resolve = |mut id: ExprId| {
+        while let Some(replacement) = replacements.get(&id) {
+            id = *replacement;
+        }
+        id
+    };
+    let exprs: Vec<ExprId> = func.exprs.iter().map(|(id, _)| id).collect();
+    for id in exprs {
+        for operand in func.exprs[id].operands_mut() {
+            *operand = resolve(*operand);
+        }
+    }
+    let blocks: Vec<BlockId> = func.blocks.iter().map(|(id, _)| id).collect();
+    for block in blocks {
+        for expr in func.blocks[block].exprs.iter_mut() {
+            *expr = resolve(*expr);
+        }
+    }
+}
+
+/// Get the operand of an `i32.eqz`.
+fn inner_operand(func: &Function, eqz: ExprId) -> ExprId {
+    match func.exprs[eqz] {
+        Expr::I32Eqz(e) => e,
+        _ => unreachable!(),
+    }
+}
@@ -0,0 +1,29 @@
+//! Remove `drop`s of values that are computed without side effects.
+//!
+//! A `drop` of a constant, a `get_local`, or of pure arithmetic over those is
+//! wasted work: both the computation of the value and the `drop` itself can
+//! go. Anything that might trap or write state is left alone.
+
+use crate::function::Function;
+use crate::ir::{BlockId, Expr, ExprId};
+use std::collections::HashSet;
+
+/// Run this pass over the given function.
+pub fn run(func: &mut Function) {
+    let dead: HashSet<ExprId> = func
+        .exprs
+        .iter()
+        .filter_map(|(id, expr)| match expr {
+            Expr::Drop(value) if !func.has_side_effects(*value) => Some(id),
+            _ => None,
+        })
+        .collect();
+    if dead.is_empty() {
+        return;
+    }
+
+    let blocks: Vec<BlockId> = func.blocks.iter().map(|(id, _)| id).collect();
+    for block in blocks {
+        func.blocks[block].exprs.retain(|e| !dead.contains(e));
+    }
+}
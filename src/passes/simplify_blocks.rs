@@ -0,0 +1,96 @@
+//! Merge blocks into their only predecessor.
+//!
+//! Nested `block`s produce chains like
+//!
+//! ```text
+//! block_1():
+//!   (set_local 0 (i32.const 1))
+//!   (br block_3 ())
+//!
+//! block_3():
+//!   (br block_2 ())
+//! ```
+//!
+//! When a block is only ever jumped to by an unconditional `br` at the end of
+//! a single other block, it can be spliced onto the end of that block
+//! instead. Blocks that are targeted by any other branch, that take
+//! parameters, or that are the function's entry or exit are left alone.
+//!
+//! Blocks cannot be removed from a function's arena, so a block that has
+//! been merged away is left behind empty, without even a terminating jump.
+//! Nothing branches to it any more, so it is unreachable from the entry
+//! block and is skipped when displaying the function.
+
+use crate::function::Function;
+use crate::ir::{BlockId, Expr};
+use std::collections::HashMap;
+
+/// Run this pass over the given function.
+pub fn run(func: &mut Function) {
+    // Merging never changes how many times the remaining blocks are
+    // referenced, so the counts only need computing once.
+    let references = count_references(func);
+    let blocks: Vec<BlockId> = func.blocks.iter().map(|(id, _)| id).collect();
+    for pred in blocks {
+        // Keep going, since the merged block may itself end in a `br` to
+        // another mergeable block.
+        while let Some(succ) = mergeable_successor(func, &references, pred) {
+            let exprs = std::mem::take(&mut func.blocks[succ].exprs);
+            let pred_exprs = &mut func.blocks[pred].exprs;
+            pred_exprs.pop();
+            pred_exprs.extend(exprs);
+        }
+    }
+}
+
+/// Count how many times each block is referenced by any branch expression.
+fn count_references(func: &Function) -> HashMap<BlockId, usize> {
+    let mut references = HashMap::new();
+    let mut add = |block: BlockId| *references.entry(block).or_insert(0) += 1;
+    for (_, expr) in func.exprs.iter() {
+        match expr {
+            Expr::Br { block, .. } | Expr::BrIf { block, .. } => add(*block),
+            Expr::IfElse {
+                consequent,
+                alternative,
+                ..
+            } => {
+                add(*consequent);
+                add(*alternative);
+            }
+            Expr::BrTable {
+                blocks, default, ..
+            } => {
+                for block in blocks.iter() {
+                    add(*block);
+                }
+                add(*default);
+            }
+            _ => {}
+        }
+    }
+    references
+}
+
+/// If `pred` ends in an argument-less `br` to a block that nothing else
+/// branches to, get that block.
+fn mergeable_successor(
+    func: &Function,
+    references: &HashMap<BlockId, usize>,
+    pred: BlockId,
+) -> Option<BlockId> {
+    let last = *func.blocks[pred].exprs.last()?;
+    let succ = match &func.exprs[last] {
+        Expr::Br { block, args } if args.is_empty() => *block,
+        _ => return None,
+    };
+    if succ == pred
+        || succ == func.entry_block()
+        || succ == func.exit_block()
+        || !func.blocks[succ].params.is_empty()
+        || references.get(&succ) != Some(&1)
+    {
+        return None;
+    }
+    Some(succ)
+}
@@ -0,0 +1,166 @@
+//! Turn chains of `br_if`s comparing a local against dense constants into a
+//! single `br_table`.
+//!
+//! A sequence like
+//!
+//! ```text
+//! (br_if (i32.eq (get_local 0) (i32.const 1)) block_a ())
+//! (br_if (i32.eq (get_local 0) (i32.const 2)) block_b ())
+//! (br_if (i32.eq (get_local 0) (i32.const 3)) block_c ())
+//! ...rest
+//! ```
+//!
+//! becomes
+//!
+//! ```text
+//! (br_table (i32.sub (get_local 0) (i32.const 1)) [block_a block_b block_c] block_rest ())
+//! ```
+//!
+//! where `block_rest` is a new block holding the rest of the original block.
+
+use crate::function::Function;
+use crate::ir::{Block, BlockId, Expr, ExprId};
+use crate::ValType;
+use std::collections::HashMap;
+
+/// The minimum number of cases in a chain before it is turned into a
+/// `br_table`.
+const MIN_CASES: usize = 3;
+
+/// Run this pass over the given function.
+pub fn run(func: &mut Function) {
+    let blocks: Vec<BlockId> = func.blocks.iter().map(|(id, _)| id).collect();
+    for block in blocks {
+        // Converting a chain splits its block, so keep going over the new
+        // continuation block until there are no more chains to convert.
+        let mut block = block;
+        while let Some(rest) = convert_first_chain(func, block) {
+            block = rest;
+        }
+    }
+}
+
+/// A single `br_if` arm of a chain:
+/// `(br_if (i32.eq (get_local local) (i32.const case)) target ())`.
+struct Arm {
+    local: u32,
+    case: i32,
+    target: BlockId,
+}
+
+fn arm(func: &Function, expr: ExprId) -> Option<Arm> {
+    let (condition, target) = match &func.exprs[expr] {
+        Expr::BrIf {
+            condition,
+            block,
+            args,
+        } if args.is_empty() => (*condition, *block),
+        _ => return None,
+    };
+    let (lhs, rhs) = match &func.exprs[condition] {
+        Expr::I32Eq(lhs, rhs) => (*lhs, *rhs),
+        _ => return None,
+    };
+    let (local, case) = match (&func.exprs[lhs], &func.exprs[rhs]) {
+        (Expr::GetLocal { local, .. }, Expr::I32Const(case))
+        | (Expr::I32Const(case), Expr::GetLocal { local, .. }) => (*local, *case),
+        _ => return None,
+    };
+    Some(Arm {
+        local,
+        case,
+        target,
+    })
+}
+
+/// Find the first dense chain in the given block and turn it into a
+/// `br_table`, returning the new block holding the rest of the original block.
+fn convert_first_chain(func: &mut Function, block: BlockId) -> Option<BlockId> {
+    let exprs = func.blocks[block].exprs.clone();
+
+    let mut start = 0;
+    while start < exprs.len() {
+        let mut arms: Vec<Arm> = vec![];
+        for expr in &exprs[start..] {
+            match arm(func, *expr) {
+                Some(a) => {
+                    // Stop at an arm for a different local, or for a case we
+                    // have already seen (whose branch can never be taken).
+                    if arms.iter().any(|b| b.local != a.local || b.case == a.case) {
+                        break;
+                    }
+                    arms.push(a);
+                }
+                None => break,
+            }
+        }
+
+        let len = arms.len();
+        if len >= MIN_CASES {
+            if let Some(targets) = dense_targets(&arms) {
+                let min = arms.iter().map(|a| a.case).min().unwrap();
+                return Some(replace_chain(
+                    func,
+                    block,
+                    start..start + len,
+                    arms[0].local,
+                    min,
+                    targets,
+                ));
+            }
+        }
+
+        start += len.max(1);
+    }
+
+    None
+}
+
+/// If the arms' cases form a contiguous range, get the targets ordered by case.
+fn dense_targets(arms: &[Arm]) -> Option<Vec<BlockId>> {
+    let min = arms.iter().map(|a| a.case as i64).min().unwrap();
+    let by_case: HashMap<i64, BlockId> = arms
+        .iter()
+        .map(|a| (a.case as i64 - min, a.target))
+        .collect();
+    (0..arms.len() as i64)
+        .map(|i| by_case.get(&i).cloned())
+        .collect()
+}
+
+fn replace_chain(
+    func: &mut Function,
+    block: BlockId,
+    chain: std::ops::Range<usize>,
+    local: u32,
+    min: i32,
+    targets: Vec<BlockId>,
+) -> BlockId {
+    let rest = func.blocks[block].exprs.split_off(chain.end);
+    func.blocks[block].exprs.truncate(chain.start);
+
+    let default = func.blocks.alloc(Block::new(
+        "br_if chain continuation",
+        vec![].into_boxed_slice(),
+    ));
+    func.blocks[default].exprs = rest;
+
+    let mut which = func.exprs.alloc(Expr::GetLocal {
+        ty: ValType::I32,
+        local,
+    });
+    if min != 0 {
+        let min = func.exprs.alloc(Expr::I32Const(min));
+        which = func.exprs.alloc(Expr::I32Sub(which, min));
+    }
+
+    let br_table = func.exprs.alloc(Expr::BrTable {
+        which,
+        blocks: targets.into_boxed_slice(),
+        default,
+        args: vec![].into_boxed_slice(),
+    });
+    func.blocks[block].exprs.push(br_table);
+
+    default
+}
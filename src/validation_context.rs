@@ -42,7 +42,35 @@ impl<'a> ValidationContext<'a> {
                 .collect(),
         };
 
+        // Imports come first in each index space, before the module's own
+        // definitions.
         let mut funcs = vec![];
+        let mut tables = vec![];
+        let mut mems = vec![];
+        let mut globals = vec![];
+        if let Some(is) = module.import_section() {
+            for i in is.entries() {
+                match i.external() {
+                    elements::External::Function(idx) => {
+                        let ty = types.get(*idx as usize).cloned().ok_or_else(|| {
+                            ErrorKind::InvalidWasm
+                                .context("imported function referring to an out-of-bounds type")
+                        })?;
+                        funcs.push(ty);
+                    }
+                    elements::External::Table(t) => {
+                        validate_table(t)?;
+                        tables.push(t.clone());
+                    }
+                    elements::External::Memory(m) => {
+                        validate_memory(m)?;
+                        mems.push(m.clone());
+                    }
+                    elements::External::Global(g) => globals.push(*g),
+                }
+            }
+        }
+
         if let Some(fs) = module.function_section() {
             funcs.reserve(fs.entries().len());
             for f in fs.entries() {
@@ -53,7 +81,6 @@ impl<'a> ValidationContext<'a> {
             }
         }
 
-        let mut tables = vec![];
         if let Some(ts) = module.table_section() {
             for t in ts.entries() {
                 validate_table(t)?;
@@ -61,7 +88,6 @@ impl<'a> ValidationContext<'a> {
             }
         }
 
-        let mut mems = vec![];
         if let Some(ms) = module.memory_section() {
             for m in ms.entries() {
                 validate_memory(m)?;
@@ -69,15 +95,9 @@ impl<'a> ValidationContext<'a> {
             }
         }
 
-        let globals = module.global_section().map_or_else(
-            || vec![],
-            |gs| {
-                gs.entries()
-                    .iter()
-                    .map(|g| g.global_type().clone())
-                    .collect()
-            },
-        );
+        if let Some(gs) = module.global_section() {
+            globals.extend(gs.entries().iter().map(|g| *g.global_type()));
+        }
 
         Ok(ValidationContext {
             types: ChunkList::with_head(types),
@@ -168,6 +188,18 @@ impl<'a> ValidationContext<'a> {
         }
     }
 
+    /// A context for code nested inside the given labels, from the
+    /// outermost to the innermost.
+    pub(crate) fn for_labels(&self, labels: &[elements::BlockType]) -> ValidationContext<'_> {
+        ValidationContext {
+            labels: ChunkList::with_head_and_tail(
+                labels.iter().rev().cloned().collect(),
+                &self.labels,
+            ),
+            ..self.nested()
+        }
+    }
+
     /// Get the type of the n^th local.
     pub fn local(&self, n: u32) -> Result<ValType> {
         self.locals
@@ -184,6 +216,32 @@ impl<'a> ValidationContext<'a> {
             })
     }
 
+    /// Get the type of the n^th global.
+    pub fn global(&self, n: u32) -> Result<&elements::GlobalType> {
+        self.globals.get(n as usize).ok_or_else(|| {
+            ErrorKind::InvalidWasm
+                .context(format!(
+                    "global {} is out of bounds ({} globals)",
+                    n,
+                    self.globals.len()
+                ))
+                .into()
+        })
+    }
+
+    /// Get the type of the n^th memory.
+    pub fn memory(&self, n: u32) -> Result<&elements::MemoryType> {
+        self.mems.get(n as usize).ok_or_else(|| {
+            ErrorKind::InvalidWasm
+                .context(format!(
+                    "memory {} is out of bounds ({} memories)",
+                    n,
+                    self.mems.len()
+                ))
+                .into()
+        })
+    }
+
     /// Get the type of the n^th local.
     pub fn label(&self, n: u32) -> Result<elements::BlockType> {
         self.labels.get(n as usize).cloned().ok_or_else(|| {
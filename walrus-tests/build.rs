@@ -23,11 +23,15 @@ fn require_wat2wasm() {
 }
 
 fn wat2wasm(path: &Path) -> PathBuf {
+    wat2wasm_with_args(path, &[])
+}
+
+fn wat2wasm_with_args(path: &Path, args: &[&str]) -> PathBuf {
     let mut wasm = PathBuf::from(path);
     wasm.set_extension("wasm");
 
     let mut cmd = Command::new("wat2wasm");
-    cmd.arg(path).arg("-o").arg(&wasm).arg("-v");
+    cmd.arg(path).arg("-o").arg(&wasm).arg("-v").args(args);
     println!("running: {:?}", cmd);
     let status = cmd.status().expect("should spawn wat2wasm OK");
     assert!(status.success(), "should run wat2wasm OK");
@@ -78,6 +82,27 @@ fn valid() {
         .expect("should write generated valid.rs file OK");
 }
 
+fn invalid() {
+    let mut invalid_tests = String::new();
+
+    for_each_wat_file("tests/invalid", |path| {
+        // These fixtures are intentionally invalid, so don't let `wat2wasm`
+        // reject them.
+        let wasm = wat2wasm_with_args(path, &["--no-check"]);
+        let test_name = path_to_ident(path);
+        invalid_tests.push_str(&format!(
+            "assert_invalid!({}, \"{}\", \"{}\");\n",
+            test_name,
+            wasm.display(),
+            path.display()
+        ));
+    });
+
+    let out_dir = env::var("OUT_DIR").unwrap();
+    fs::write(Path::new(&out_dir).join("invalid.rs"), &invalid_tests)
+        .expect("should write generated invalid.rs file OK");
+}
+
 fn ir() {
     let mut ir_tests = String::new();
 
@@ -97,11 +122,48 @@ fn ir() {
         .expect("should write generated ir.rs file OK");
 }
 
+fn passes() {
+    let mut pass_tests = String::new();
+
+    for_each_wat_file("tests/passes", |path| {
+        let wasm = wat2wasm(path);
+        let test_name = path_to_ident(path);
+        let pass = path
+            .parent()
+            .and_then(|p| p.file_name())
+            .expect("pass tests should live in a directory named after their pass")
+            .to_string_lossy()
+            .replace('-', "_");
+        pass_tests.push_str(&format!(
+            "assert_pass!({}, {}, \"{}\", \"{}\");\n",
+            test_name,
+            pass,
+            wasm.display(),
+            path.display()
+        ));
+    });
+
+    let out_dir = env::var("OUT_DIR").unwrap();
+    fs::write(Path::new(&out_dir).join("passes.rs"), &pass_tests)
+        .expect("should write generated passes.rs file OK");
+}
+
+fn analysis() {
+    // The analysis tests are written by hand, so just make sure that their
+    // fixtures are compiled.
+    for_each_wat_file("tests/analysis", |path| {
+        wat2wasm(path);
+    });
+}
+
 fn main() {
     println!("cargo:rerun-if-changed=build.rs");
     println!("cargo:rerun-if-env-changed=WALRUS_TESTS_DOT");
 
     require_wat2wasm();
     valid();
+    invalid();
     ir();
+    passes();
+    analysis();
 }
@@ -0,0 +1,327 @@
+extern crate parity_wasm;
+extern crate walrus;
+
+use parity_wasm::elements;
+use walrus::function::Function;
+
+fn functions(wasm_path: &str) -> Vec<Function> {
+    let module = elements::deserialize_file(wasm_path).unwrap();
+    let type_section = module.type_section().unwrap();
+    let func_section = module.function_section().unwrap();
+    let code_section = module.code_section().unwrap();
+
+    let validation = walrus::validation_context::ValidationContext::for_module(&module)
+        .expect("could not create validation context");
+
+    func_section
+        .entries()
+        .iter()
+        .zip(code_section.bodies().iter())
+        .map(|(func, body)| {
+            Function::new(&validation, type_section, func, body)
+                .expect("constructing a new `walrus::Function` failed")
+        })
+        .collect()
+}
+
+#[test]
+fn arg_usage() {
+    let funcs = functions("tests/analysis/arg-usage.wasm");
+    let usage = walrus::analysis::arg_usage::compute(&funcs[0]);
+    assert_eq!(usage.len(), 2);
+    assert_eq!(usage[&0], 2);
+    assert_eq!(usage[&1], 0);
+}
+
+#[test]
+fn arg_usage_unreachable() {
+    let funcs = functions("tests/analysis/arg-usage-unreachable.wasm");
+    let usage = walrus::analysis::arg_usage::compute(&funcs[0]);
+    assert_eq!(usage[&0], 1);
+    assert_eq!(usage[&1], 0);
+}
+
+#[test]
+fn estimated_cost() {
+    let funcs = functions("tests/analysis/cost.wasm");
+    let model = walrus::analysis::cost::CostModel::default();
+    let adds = funcs[0].estimated_cost(&model);
+    let div = funcs[1].estimated_cost(&model);
+    assert!(div > adds, "div cost {} <= adds cost {}", div, adds);
+}
+
+#[test]
+fn estimated_cost_ignores_dead_code() {
+    let funcs = functions("tests/analysis/cost-unreachable.wasm");
+    let model = walrus::analysis::cost::CostModel::default();
+    assert_eq!(
+        funcs[0].estimated_cost(&model),
+        funcs[1].estimated_cost(&model)
+    );
+}
+
+#[test]
+fn operand_stack() {
+    use walrus::analysis::operand_stack;
+    use walrus::ir::Expr;
+    use walrus::ValType;
+
+    let funcs = functions("tests/analysis/operand-stack.wasm");
+    let func = &funcs[0];
+    let entry = func.block(func.entry_block());
+
+    let drop = entry.exprs()[0];
+    let value = match func.expr(drop) {
+        Expr::Drop(value) => *value,
+        e => panic!("expected a drop, found {:?}", e),
+    };
+    assert_eq!(
+        operand_stack::at(func, drop),
+        Some(vec![Some(ValType::I32), Some(ValType::F32)])
+    );
+    assert_eq!(
+        operand_stack::at(func, value),
+        Some(vec![Some(ValType::I32)])
+    );
+}
+
+#[test]
+fn operand_stack_unreachable() {
+    use walrus::analysis::operand_stack;
+    use walrus::ValType;
+
+    let funcs = functions("tests/analysis/operand-stack-unreachable.wasm");
+    let func = &funcs[0];
+    let entry = func.block(func.entry_block());
+
+    // The `select` of unknown operands pushes an operand of unknown type,
+    // which still counts towards the stack's height.
+    let drop = entry.exprs()[1];
+    assert_eq!(
+        operand_stack::at(func, drop),
+        Some(vec![None, Some(ValType::I32)])
+    );
+}
+
+/// Parse every function in the given module from its original encoding.
+fn raw_functions(wasm: &[u8]) -> Vec<Function> {
+    let module: elements::Module = elements::deserialize_buffer(wasm).unwrap();
+    let validation = walrus::validation_context::ValidationContext::for_module(&module)
+        .expect("could not create validation context");
+    let bodies = walrus::function::raw_bodies(wasm).unwrap();
+    module
+        .function_section()
+        .unwrap()
+        .entries()
+        .iter()
+        .zip(bodies)
+        .map(|(func, raw)| {
+            Function::from_raw(&validation, module.type_section().unwrap(), func, raw)
+                .expect("constructing a new `walrus::Function` failed")
+        })
+        .collect()
+}
+
+/// Get the original offsets of the values of the first `set_local`s in the
+/// function's entry block, and of the `set_local`s themselves.
+fn set_local_offsets(func: &Function, n: usize) -> Vec<Option<usize>> {
+    use walrus::ir::Expr;
+
+    let entry = func.block(func.entry_block());
+    entry.exprs()[..n]
+        .iter()
+        .flat_map(|set| {
+            let value = match func.expr(*set) {
+                Expr::SetLocal { value, .. } => *value,
+                e => panic!("expected a set_local, found {:?}", e),
+            };
+            vec![func.original_offset(value), func.original_offset(*set)]
+        })
+        .collect()
+}
+
+#[test]
+fn original_offsets() {
+    let wasm = std::fs::read("tests/analysis/offsets.wasm").unwrap();
+    let funcs = raw_functions(&wasm);
+
+    // The code section starts with the number of bodies, then the body's
+    // size and its (empty) locals. After that, `i32.const 1` is two bytes,
+    // `set_local 0` is two bytes, and `i32.const 300` is three bytes.
+    assert_eq!(
+        set_local_offsets(&funcs[0], 2),
+        vec![Some(3), Some(5), Some(7), Some(10)]
+    );
+
+    // Without the original encoding, there are no original offsets.
+    let funcs = functions("tests/analysis/offsets.wasm");
+    assert_eq!(set_local_offsets(&funcs[0], 2), vec![None; 4]);
+}
+
+#[test]
+fn original_offsets_non_canonical() {
+    #[rustfmt::skip]
+    let wasm = [
+        0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00,
+        // type section: (func (param i32))
+        0x01, 0x05, 0x01, 0x60, 0x01, 0x7f, 0x00,
+        // function section
+        0x03, 0x02, 0x01, 0x00,
+        // code section
+        0x0a, 0x10, 0x01, 0x0e, 0x00,
+        // i32.const 1, padded to five bytes
+        0x41, 0x81, 0x80, 0x80, 0x00,
+        // set_local 0, padded to two bytes
+        0x21, 0x80, 0x00,
+        // i32.const 2
+        0x41, 0x02,
+        // set_local 0
+        0x21, 0x00,
+        0x0b,
+    ];
+    let funcs = raw_functions(&wasm);
+    assert_eq!(
+        set_local_offsets(&funcs[0], 2),
+        vec![Some(3), Some(8), Some(11), Some(13)]
+    );
+}
+
+#[test]
+fn for_each_mem_arg_mut() {
+    let mut funcs = functions("tests/analysis/mem-args.wasm");
+    let func = &mut funcs[0];
+
+    let mut count = 0;
+    func.for_each_mem_arg_mut(|arg| {
+        arg.offset += 16;
+        count += 1;
+    });
+    assert_eq!(count, 3);
+
+    let ir = func.to_string();
+    assert!(
+        ir.contains("(i32.store offset=16 (get_local 0) (i32.load offset=20 (get_local 0)))"),
+        "unexpected IR:\n{}",
+        ir
+    );
+    assert!(
+        ir.contains("(i32.load8_u offset=17 (get_local 0))"),
+        "unexpected IR:\n{}",
+        ir
+    );
+}
+
+#[test]
+fn for_each_atomic_mem_arg_mut() {
+    let mut funcs = functions("tests/analysis/atomic-accesses.wasm");
+    let func = &mut funcs[0];
+
+    let mut count = 0;
+    func.for_each_mem_arg_mut(|arg| {
+        arg.offset += 8;
+        count += 1;
+    });
+    assert_eq!(count, 3);
+
+    let ir = func.to_string();
+    for access in &[
+        "(i32.atomic.rmw8_u.cmpxchg offset=8 ",
+        "(i64.atomic.wait offset=8 ",
+        "(atomic.notify offset=8 ",
+    ] {
+        assert!(ir.contains(access), "unexpected IR:\n{}", ir);
+    }
+}
+
+#[test]
+fn for_each_mem_arg_mut_after_dce() {
+    let mut funcs = functions("tests/analysis/dead-access.wasm");
+    let func = &mut funcs[0];
+    walrus::passes::dce::run(func);
+
+    let mut offsets = vec![];
+    func.for_each_mem_arg_mut(|arg| offsets.push(arg.offset));
+    assert_eq!(offsets, vec![4]);
+}
+
+#[test]
+fn memory_accesses() {
+    use walrus::analysis::memory_accesses::{self, Access};
+    use walrus::ir::{LoadKind, StoreKind};
+
+    let funcs = functions("tests/analysis/mem-args.wasm");
+    let accesses = memory_accesses::compute(&funcs[0]);
+    assert_eq!(accesses.len(), 3);
+
+    assert_eq!(accesses[0].access, Access::Load(LoadKind::I32));
+    assert!(!accesses[0].is_write());
+    assert_eq!(accesses[0].width(), 4);
+    assert_eq!(accesses[0].arg.offset, 4);
+
+    assert_eq!(accesses[1].access, Access::Store(StoreKind::I32));
+    assert!(accesses[1].is_write());
+    assert_eq!(accesses[1].width(), 4);
+    assert_eq!(accesses[1].arg.offset, 0);
+
+    assert_eq!(
+        accesses[2].access,
+        Access::Load(LoadKind::I32_8 { signed: false })
+    );
+    assert!(!accesses[2].is_write());
+    assert_eq!(accesses[2].width(), 1);
+    assert_eq!(accesses[2].arg.offset, 1);
+}
+
+#[test]
+fn memory_accesses_in_select() {
+    use walrus::analysis::memory_accesses;
+
+    // The loads are evaluated in the order they are pushed, even though the
+    // `select` chooses between them.
+    let funcs = functions("tests/analysis/select-accesses.wasm");
+    let offsets: Vec<u32> = memory_accesses::compute(&funcs[0])
+        .iter()
+        .map(|a| a.arg.offset)
+        .collect();
+    assert_eq!(offsets, vec![4, 8, 12]);
+}
+
+#[test]
+fn atomic_memory_accesses() {
+    use walrus::analysis::memory_accesses::{self, Access};
+    use walrus::ir::AtomicWidth;
+
+    let funcs = functions("tests/analysis/atomic-accesses.wasm");
+    let accesses = memory_accesses::compute(&funcs[0]);
+    assert_eq!(accesses.len(), 3);
+
+    assert_eq!(accesses[0].access, Access::Rmw(AtomicWidth::I32_8));
+    assert!(accesses[0].is_write());
+    assert_eq!(accesses[0].width(), 1);
+
+    assert_eq!(accesses[1].access, Access::Wait { sixty_four: true });
+    assert!(!accesses[1].is_write());
+    assert_eq!(accesses[1].width(), 8);
+
+    assert_eq!(accesses[2].access, Access::Notify);
+    assert!(!accesses[2].is_write());
+    assert_eq!(accesses[2].width(), 4);
+}
+
+#[test]
+fn memory_accesses_after_dce() {
+    use walrus::analysis::memory_accesses;
+
+    let mut funcs = functions("tests/analysis/dead-access.wasm");
+    let func = &mut funcs[0];
+    let offsets = |func: &Function| -> Vec<u32> {
+        memory_accesses::compute(func)
+            .iter()
+            .map(|a| a.arg.offset)
+            .collect()
+    };
+    assert_eq!(offsets(func), vec![4, 8]);
+
+    walrus::passes::dce::run(func);
+    assert_eq!(offsets(func), vec![4]);
+}
@@ -0,0 +1,9 @@
+(module
+  (type (;0;) (func (param i32 i32) (result i32)))
+  (func (;0;) (type 0)
+    get_local 0
+    drop
+    block
+      unreachable
+    end
+    get_local 1))
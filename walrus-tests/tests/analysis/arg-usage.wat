@@ -0,0 +1,8 @@
+(module
+  (type (;0;) (func (param i32 i32) (result i32)))
+  (func (;0;) (type 0) (local i32)
+    get_local 0
+    get_local 0
+    i32.add
+    set_local 2
+    get_local 2))
@@ -0,0 +1,18 @@
+(module
+  (type (;0;) (func (param i32 i64)))
+  (func (;0;) (type 0)
+    get_local 0
+    i32.const 1
+    i32.const 2
+    i32.atomic.rmw8_u.cmpxchg
+    drop
+    get_local 0
+    get_local 1
+    get_local 1
+    i64.atomic.wait
+    drop
+    get_local 0
+    i32.const 1
+    atomic.wake
+    drop)
+  (memory (;0;) 1 1 shared))
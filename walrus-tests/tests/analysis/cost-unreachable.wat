@@ -0,0 +1,14 @@
+(module
+  (type (;0;) (func (param i32 i32) (result i32)))
+  (func (;0;) (type 0)
+    block
+      unreachable
+    end
+    get_local 0
+    get_local 1
+    i32.div_s)
+  (func (;1;) (type 0)
+    block
+      unreachable
+    end
+    i32.const 0))
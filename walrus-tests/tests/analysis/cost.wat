@@ -0,0 +1,12 @@
+(module
+  (type (;0;) (func (param i32 i32) (result i32)))
+  (func (;0;) (type 0)
+    get_local 0
+    get_local 1
+    i32.add
+    get_local 1
+    i32.add)
+  (func (;1;) (type 0)
+    get_local 0
+    get_local 1
+    i32.div_s))
@@ -0,0 +1,11 @@
+(module
+  (type (;0;) (func (param i32) (result i32)))
+  (func (;0;) (type 0)
+    get_local 0
+    i32.load offset=4
+    return
+    get_local 0
+    i32.const 0
+    i32.store offset=8
+    i32.const 0)
+  (memory (;0;) 1))
@@ -0,0 +1,8 @@
+(module
+  (type (;0;) (func (param i32) (result i32)))
+  (func (;0;) (type 0)
+    i32.const 1
+    set_local 0
+    i32.const 300
+    set_local 0
+    get_local 0))
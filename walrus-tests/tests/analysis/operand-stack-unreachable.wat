@@ -0,0 +1,8 @@
+(module
+  (type (;0;) (func))
+  (func (;0;) (type 0)
+    unreachable
+    select
+    i32.const 1
+    drop
+    drop))
@@ -0,0 +1,8 @@
+(module
+  (type (;0;) (func (param i32 f32) (result i32)))
+  (func (;0;) (type 0)
+    i32.const 1
+    get_local 1
+    drop
+    get_local 0
+    i32.add))
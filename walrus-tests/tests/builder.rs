@@ -0,0 +1,97 @@
+extern crate failure;
+extern crate parity_wasm;
+extern crate walrus;
+
+use parity_wasm::elements::Instruction;
+use walrus::function::FunctionBuilder;
+use walrus::ValType;
+
+#[test]
+fn build_inc() -> Result<(), failure::Error> {
+    let mut builder = FunctionBuilder::new(&[ValType::I32], Some(ValType::I32));
+    builder.get_local(0)?.i32_const(1)?.i32_add()?;
+    let func = builder.finish()?;
+    let ir = func.to_string();
+    assert!(
+        ir.contains("(return ((i32.add (get_local 0) (i32.const 1))))"),
+        "unexpected IR:\n{}",
+        ir
+    );
+    Ok(())
+}
+
+#[test]
+fn build_with_locals_and_control_flow() -> Result<(), failure::Error> {
+    let mut builder = FunctionBuilder::new(&[ValType::I32], None);
+    let tmp = builder.add_local(ValType::I32);
+    assert_eq!(tmp, 1);
+    builder
+        .get_local(0)?
+        .if_(Some(ValType::I32))?
+        .i32_const(1)?
+        .else_()?
+        .i32_const(2)?
+        .end()?
+        .set_local(tmp)?;
+    builder.finish()?;
+    Ok(())
+}
+
+#[test]
+fn build_type_mismatch() -> Result<(), failure::Error> {
+    let mut builder = FunctionBuilder::new(&[], Some(ValType::I32));
+    builder.i32_const(1)?;
+    assert!(builder.i32_add().is_err());
+
+    // The rejected instruction is not added, so the body is still complete.
+    builder.finish()?;
+    Ok(())
+}
+
+#[test]
+fn build_missing_result() {
+    let builder = FunctionBuilder::new(&[], Some(ValType::I32));
+    assert!(builder.finish().is_err());
+}
+
+#[test]
+fn build_open_frames() -> Result<(), failure::Error> {
+    let mut builder = FunctionBuilder::new(&[ValType::I32], Some(ValType::I32));
+    builder
+        .block(Some(ValType::I32))?
+        .get_local(0)?
+        .if_(Some(ValType::I32))?
+        .i32_const(1)?;
+    assert!(
+        builder.end().is_err(),
+        "an `if` with a result needs an `else`"
+    );
+    builder.else_()?.i32_const(2)?.end()?;
+    assert!(builder.else_().is_err());
+    builder.end()?;
+    assert!(builder.end().is_err(), "there is no frame left to end");
+    builder.finish()?;
+    Ok(())
+}
+
+#[test]
+fn build_memory_access() -> Result<(), failure::Error> {
+    let load = Instruction::I32Load(2, 0);
+
+    let mut builder = FunctionBuilder::new(&[], Some(ValType::I32));
+    builder.i32_const(0)?;
+    assert!(builder.instr(load.clone()).is_err());
+
+    let mut builder = FunctionBuilder::new(&[], Some(ValType::I32)).with_memory(1, None);
+    builder.i32_const(0)?.instr(load)?;
+    builder.finish()?;
+    Ok(())
+}
+
+#[test]
+fn build_unclosed_block() -> Result<(), failure::Error> {
+    let mut builder = FunctionBuilder::new(&[], None);
+    builder.block(None)?.i32_const(1)?.drop()?;
+    assert!(builder.finish().is_err());
+    Ok(())
+}
@@ -0,0 +1,136 @@
+extern crate failure;
+extern crate parity_wasm;
+extern crate walrus;
+
+use parity_wasm::elements::Instruction;
+use walrus::dot::Dot;
+use walrus::error::ErrorKind;
+use walrus::function::FunctionBuilder;
+use walrus::ir::matcher::{find, BrMatcher};
+use walrus::ir::Expr;
+use walrus::ValType;
+
+#[test]
+fn params() -> Result<(), failure::Error> {
+    let mut builder = FunctionBuilder::new(&[ValType::I32, ValType::I64], None);
+    let local = builder.add_local(ValType::F32);
+    let func = builder.finish()?;
+    assert_eq!(func.params(), &[ValType::I32, ValType::I64][..]);
+    assert!(func.is_param(0));
+    assert!(func.is_param(1));
+    assert!(!func.is_param(local));
+    Ok(())
+}
+
+#[test]
+fn ids_from_another_function() -> Result<(), failure::Error> {
+    let mut builder = FunctionBuilder::new(&[], Some(ValType::I32));
+    builder.i32_const(1)?;
+    let func = builder.finish()?;
+
+    let mut builder = FunctionBuilder::new(&[], None);
+    builder.i32_const(1)?.drop()?;
+    let other = builder.finish()?;
+
+    let block = func.entry_block();
+    assert!(func.try_block(block).is_ok());
+    let err = other.try_block(block).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!("block_{} is not a block in this function", block.index())
+    );
+
+    let expr = func.block(block).exprs()[0];
+    assert!(func.try_expr(expr).is_ok());
+    assert!(other.try_expr(expr).is_err());
+    Ok(())
+}
+
+#[test]
+fn unsupported_instruction() -> Result<(), failure::Error> {
+    let mut builder = FunctionBuilder::new(&[], None);
+    let err = builder.instr(Instruction::F32Const(0)).unwrap_err();
+    assert_eq!(err.to_string(), "`f32.const 0` is not supported yet");
+    assert!(err
+        .iter_chain()
+        .any(|c| c.downcast_ref::<ErrorKind>() == Some(&ErrorKind::Unsupported)));
+    Ok(())
+}
+
+#[test]
+fn name() -> Result<(), failure::Error> {
+    let mut builder = FunctionBuilder::new(&[ValType::I32], Some(ValType::I32));
+    builder.get_local(0)?;
+    let mut func = builder.finish()?;
+    assert_eq!(func.name(), None);
+    assert!(func.to_string().starts_with("func {\n"));
+
+    func.set_name(Some("identity"));
+    assert_eq!(func.name(), Some("identity"));
+    assert!(func.to_string().starts_with("func $identity {\n"));
+
+    func.set_name(None::<String>);
+    assert_eq!(func.name(), None);
+    Ok(())
+}
+
+#[test]
+fn escaped_name() -> Result<(), failure::Error> {
+    let mut builder = FunctionBuilder::new(&[], None);
+    builder.i32_const(1)?.drop()?;
+    let mut func = builder.finish()?;
+
+    func.set_name(Some("say \"hi\" <there>"));
+    assert!(func
+        .to_string()
+        .starts_with("func $\"say \\\"hi\\\" <there>\" {\n"));
+
+    let mut dot = vec![];
+    func.dot(&mut dot)?;
+    let dot = String::from_utf8(dot)?;
+    assert!(
+        dot.contains("label=<say &quot;hi&quot; &lt;there&gt;>;"),
+        "unexpected dot:\n{}",
+        dot
+    );
+    Ok(())
+}
+
+#[test]
+fn branch_target_params() -> Result<(), failure::Error> {
+    let mut builder = FunctionBuilder::new(&[], Some(ValType::I32));
+    builder
+        .block(Some(ValType::I32))?
+        .block(None)?
+        .i32_const(1)?
+        .br(1)?
+        .end()?
+        .i32_const(2)?
+        .end()?;
+    let func = builder.finish()?;
+
+    let target = find(&func, &BrMatcher::new())
+        .into_iter()
+        .filter_map(|e| match func.expr(e) {
+            Expr::Br { block, args } if !args.is_empty() => Some(*block),
+            _ => None,
+        })
+        .find(|b| *b != func.exit_block())
+        .expect("should branch out of the outer block with a value");
+    assert_eq!(func.block(target).params(), &[ValType::I32][..]);
+    Ok(())
+}
+
+#[test]
+fn locals() -> Result<(), failure::Error> {
+    let mut builder = FunctionBuilder::new(&[ValType::I32], None);
+    builder.add_local(ValType::I64);
+    let mut func = builder.finish()?;
+    assert_eq!(func.locals(), &[ValType::I64][..]);
+
+    let local = func.add_local(ValType::F32);
+    assert_eq!(local, 2);
+    assert!(!func.is_param(local));
+    assert_eq!(func.locals(), &[ValType::I64, ValType::F32][..]);
+    Ok(())
+}
@@ -0,0 +1,48 @@
+extern crate failure;
+extern crate parity_wasm;
+extern crate walrus;
+extern crate walrus_tests;
+
+use parity_wasm::elements;
+use std::path::Path;
+
+macro_rules! assert_invalid {
+    ($name:ident, $wasm_path:expr, $wat_path:expr) => {
+        #[test]
+        fn $name() {
+            let module = elements::deserialize_file($wasm_path).unwrap();
+            let type_section = module.type_section().unwrap();
+            let func_section = module.function_section().unwrap();
+            let code_section = module.code_section().unwrap();
+
+            let validation = walrus::validation_context::ValidationContext::for_module(&module)
+                .expect("could not create validation context");
+
+            let checker = walrus_tests::FileCheck::from_file(Path::new($wat_path));
+
+            let errors: Vec<_> = func_section
+                .entries()
+                .iter()
+                .zip(code_section.bodies().iter())
+                .filter_map(|(func, body)| {
+                    walrus::function::Function::new(&validation, &type_section, func, body).err()
+                })
+                .collect();
+            assert!(
+                !errors.is_empty(),
+                "constructing a `walrus::Function` from invalid wasm should fail"
+            );
+
+            let mut output = String::new();
+            for e in errors {
+                for c in e.iter_chain() {
+                    output.push_str(&format!("{}\n", c));
+                }
+            }
+
+            checker.check(&output);
+        }
+    };
+}
+
+include!(concat!(env!("OUT_DIR"), "/invalid.rs"));
@@ -0,0 +1,9 @@
+(module
+  (type (;0;) (func))
+  (func (;0;) (type 0)
+    block
+      i32.const 1
+    end))
+
+;; CHECK: block_3 leaves 1 extra operand(s) on the stack at its end, beyond its result types
+;; NEXT:  The WebAssembly is invalid
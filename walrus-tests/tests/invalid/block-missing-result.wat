@@ -0,0 +1,9 @@
+(module
+  (type (;0;) (func (result i32)))
+  (func (;0;) (type 0)
+    block (result i32)
+    end))
+
+;; CHECK: at the end of block_3
+;; NEXT:  popped operand past control frame height in non-unreachable code
+;; NEXT:  The WebAssembly is invalid
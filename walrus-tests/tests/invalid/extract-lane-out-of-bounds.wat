@@ -0,0 +1,9 @@
+(module
+  (type (;0;) (func (param i32) (result i32)))
+  (func (;0;) (type 0)
+    get_local 0
+    i32x4.splat
+    i32x4.extract_lane 4))
+
+;; CHECK: lane index 4 is out of bounds for i32x4, which has 4 lanes
+;; NEXT:  The WebAssembly is invalid
@@ -0,0 +1,9 @@
+(module
+  (type (;0;) (func (param i32) (result i32)))
+  (func (;0;) (type 0)
+    get_local 0
+    i32.load))
+
+;; CHECK: `i32.load` without a memory
+;; NEXT:  memory 0 is out of bounds (0 memories)
+;; NEXT:  The WebAssembly is invalid
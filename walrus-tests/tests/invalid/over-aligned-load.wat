@@ -0,0 +1,9 @@
+(module
+  (type (;0;) (func (param i32) (result i32)))
+  (func (;0;) (type 0)
+    get_local 0
+    i32.load align=8)
+  (memory (;0;) 1))
+
+;; CHECK: alignment 2^3 of `i32.load` is larger than its natural alignment of 4 bytes
+;; NEXT:  The WebAssembly is invalid
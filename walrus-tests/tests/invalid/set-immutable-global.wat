@@ -0,0 +1,9 @@
+(module
+  (type (;0;) (func))
+  (global (;0;) i32 (i32.const 1))
+  (func (;0;) (type 0)
+    i32.const 2
+    set_global 0))
+
+;; CHECK: set_global of immutable global 0
+;; NEXT:  The WebAssembly is invalid
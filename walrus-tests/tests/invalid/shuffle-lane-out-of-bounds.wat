@@ -0,0 +1,12 @@
+(module
+  (type (;0;) (func (param i32)))
+  (func (;0;) (type 0)
+    get_local 0
+    i32x4.splat
+    get_local 0
+    i32x4.splat
+    v8x16.shuffle 0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 32
+    drop))
+
+;; CHECK: lane index 32 of `v8x16.shuffle` is out of bounds, it must be less than 32
+;; NEXT:  The WebAssembly is invalid
@@ -0,0 +1,9 @@
+(module
+  (type (;0;) (func (param i32) (result i32)))
+  (func (;0;) (type 0)
+    get_local 0
+    i32.atomic.load align=2)
+  (memory (;0;) 1 1 shared))
+
+;; CHECK: alignment 2^1 of `i32.atomic.load` is not its natural alignment of 4 bytes
+;; NEXT:  The WebAssembly is invalid
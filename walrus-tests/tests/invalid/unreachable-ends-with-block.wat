@@ -0,0 +1,10 @@
+(module
+  (type (;0;) (func (result i32)))
+  (func (;0;) (type 0)
+    block
+      unreachable
+    end
+    i32.add))
+
+;; CHECK: popped operand past control frame height in non-unreachable code
+;; NEXT:  The WebAssembly is invalid
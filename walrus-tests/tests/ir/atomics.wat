@@ -0,0 +1,44 @@
+(module
+  (type (;0;) (func (param i32 i64 i64) (result i32)))
+  (func (;0;) (type 0)
+    get_local 0
+    get_local 0
+    i32.atomic.load offset=4
+    i32.atomic.store8
+    get_local 0
+    get_local 1
+    i64.atomic.rmw32_u.xchg
+    drop
+    get_local 0
+    get_local 1
+    get_local 1
+    i64.atomic.rmw.cmpxchg
+    drop
+    get_local 0
+    get_local 1
+    get_local 2
+    i64.atomic.wait
+    drop
+    get_local 0
+    i32.const 1
+    atomic.wake
+    drop
+    get_local 0
+    i32.const 1
+    i32.atomic.rmw16_u.sub)
+  (memory (;0;) 1 1 shared))
+
+;; CHECK: func {
+;; NEXT:    ;; function entry
+;; NEXT:    block_1():
+;; NEXT:      (i32.atomic.store8 (get_local 0) (i32.atomic.load offset=4 (get_local 0)))
+;; NEXT:      (drop (i64.atomic.rmw32_u.xchg (get_local 0) (get_local 1)))
+;; NEXT:      (drop (i64.atomic.rmw.cmpxchg (get_local 0) (get_local 1) (get_local 1)))
+;; NEXT:      (drop (i64.atomic.wait (get_local 0) (get_local 1) (get_local 2)))
+;; NEXT:      (drop (atomic.notify (get_local 0) (i32.const 1)))
+;; NEXT:      (br block_0 ((i32.atomic.rmw16_u.sub (get_local 0) (i32.const 1))))
+;; NEXT:
+;; NEXT:    ;; function exit
+;; NEXT:    block_0(i32):
+;; NEXT:      (return ((i32.atomic.rmw16_u.sub (get_local 0) (i32.const 1))))
+;; NEXT:  }
@@ -0,0 +1,19 @@
+(module
+  (type (;0;) (func (result i32)))
+  (global (;0;) i32 (i32.const 1))
+  (global (;1;) (mut i32) (i32.const 0))
+  (func (;0;) (type 0)
+    get_global 0
+    set_global 1
+    get_global 1))
+
+;; CHECK: func {
+;; NEXT:    ;; function entry
+;; NEXT:    block_1():
+;; NEXT:      (set_global 1 (get_global 0))
+;; NEXT:      (br block_0 ((get_global 1)))
+;; NEXT:  
+;; NEXT:    ;; function exit
+;; NEXT:    block_0(i32):
+;; NEXT:      (return ((get_global 1)))
+;; NEXT:  }
@@ -0,0 +1,31 @@
+(module
+  (type (;0;) (func (param i32)))
+  (func (;0;) (type 0) (local i32)
+    get_local 0
+    if
+      i32.const 1
+      set_local 1
+    end))
+
+;; CHECK: func {
+;; NEXT:    ;; function entry
+;; NEXT:    block_1():
+;; NEXT:      (if/else (get_local 0) block_3 block_4)
+;; NEXT:
+;; NEXT:    ;; consequent
+;; NEXT:    block_3():
+;; NEXT:      (set_local 1 (i32.const 1))
+;; NEXT:      (br block_2 ())
+;; NEXT:
+;; NEXT:    ;; alternative
+;; NEXT:    block_4():
+;; NEXT:      (br block_2 ())
+;; NEXT:
+;; NEXT:    ;; if/else continuation
+;; NEXT:    block_2():
+;; NEXT:      (br block_0 ())
+;; NEXT:
+;; NEXT:    ;; function exit
+;; NEXT:    block_0():
+;; NEXT:      (return ())
+;; NEXT:  }
@@ -0,0 +1,21 @@
+(module
+  (type (;0;) (func (param i32) (result i32)))
+  (import "env" "memory" (memory (;0;) 1))
+  (func (;0;) (type 0)
+    get_local 0
+    get_local 0
+    i32.load offset=8
+    i32.store
+    get_local 0
+    i32.load16_s))
+
+;; CHECK: func {
+;; NEXT:    ;; function entry
+;; NEXT:    block_1():
+;; NEXT:      (i32.store (get_local 0) (i32.load offset=8 (get_local 0)))
+;; NEXT:      (br block_0 ((i32.load16_s (get_local 0))))
+;; NEXT:
+;; NEXT:    ;; function exit
+;; NEXT:    block_0(i32):
+;; NEXT:      (return ((i32.load16_s (get_local 0))))
+;; NEXT:  }
@@ -0,0 +1,26 @@
+(module
+  (type (;0;) (func (param i32) (result i32)))
+  (func (;0;) (type 0)
+    get_local 0
+    get_local 0
+    i32.load offset=4
+    i32.store8 offset=1 align=1
+    get_local 0
+    get_local 0
+    i64.load16_s align=1
+    i64.store32 align=2
+    get_local 0
+    i32.load8_u)
+  (memory (;0;) 1))
+
+;; CHECK: func {
+;; NEXT:    ;; function entry
+;; NEXT:    block_1():
+;; NEXT:      (i32.store8 offset=1 (get_local 0) (i32.load offset=4 (get_local 0)))
+;; NEXT:      (i64.store32 align=2 (get_local 0) (i64.load16_s align=1 (get_local 0)))
+;; NEXT:      (br block_0 ((i32.load8_u (get_local 0))))
+;; NEXT:
+;; NEXT:    ;; function exit
+;; NEXT:    block_0(i32):
+;; NEXT:      (return ((i32.load8_u (get_local 0))))
+;; NEXT:  }
@@ -0,0 +1,36 @@
+(module
+  (type (;0;) (func (param i32)))
+  (func (;0;) (type 0)
+    block
+      block
+      end
+      i32.const 2
+      set_local 0
+    end))
+
+;; CHECK: func {
+;; NEXT:    ;; function entry
+;; NEXT:    block_1():
+;; NEXT:      (br block_3 ())
+;; NEXT:
+;; NEXT:    ;; block
+;; NEXT:    block_3():
+;; NEXT:      (br block_5 ())
+;; NEXT:
+;; NEXT:    ;; block
+;; NEXT:    block_5():
+;; NEXT:      (br block_4 ())
+;; NEXT:
+;; NEXT:    ;; block continuation
+;; NEXT:    block_4():
+;; NEXT:      (set_local 0 (i32.const 2))
+;; NEXT:      (br block_2 ())
+;; NEXT:
+;; NEXT:    ;; block continuation
+;; NEXT:    block_2():
+;; NEXT:      (br block_0 ())
+;; NEXT:
+;; NEXT:    ;; function exit
+;; NEXT:    block_0():
+;; NEXT:      (return ())
+;; NEXT:  }
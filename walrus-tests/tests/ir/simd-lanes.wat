@@ -0,0 +1,18 @@
+(module
+  (type (;0;) (func (param i32) (result i32)))
+  (func (;0;) (type 0)
+    get_local 0
+    i32x4.splat
+    i32.const 7
+    i32x4.replace_lane 1
+    i32x4.extract_lane 2))
+
+;; CHECK: func {
+;; NEXT:    ;; function entry
+;; NEXT:    block_1():
+;; NEXT:      (br block_0 ((i32x4.extract_lane 2 (i32x4.replace_lane 1 (i32x4.splat (get_local 0)) (i32.const 7)))))
+;; NEXT:
+;; NEXT:    ;; function exit
+;; NEXT:    block_0(i32):
+;; NEXT:      (return ((i32x4.extract_lane 2 (i32x4.replace_lane 1 (i32x4.splat (get_local 0)) (i32.const 7)))))
+;; NEXT:  }
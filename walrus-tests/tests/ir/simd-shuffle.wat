@@ -0,0 +1,19 @@
+(module
+  (type (;0;) (func (param i32 i32) (result i32)))
+  (func (;0;) (type 0)
+    get_local 0
+    i32x4.splat
+    get_local 1
+    i32x4.splat
+    v8x16.shuffle 0 1 2 3 16 17 18 19 4 5 6 7 31 30 29 28
+    i32x4.extract_lane 3))
+
+;; CHECK: func {
+;; NEXT:    ;; function entry
+;; NEXT:    block_1():
+;; NEXT:      (br block_0 ((i32x4.extract_lane 3 (v8x16.shuffle 0 1 2 3 16 17 18 19 4 5 6 7 31 30 29 28 (i32x4.splat (get_local 0)) (i32x4.splat (get_local 1))))))
+;; NEXT:
+;; NEXT:    ;; function exit
+;; NEXT:    block_0(i32):
+;; NEXT:      (return ((i32x4.extract_lane 3 (v8x16.shuffle 0 1 2 3 16 17 18 19 4 5 6 7 31 30 29 28 (i32x4.splat (get_local 0)) (i32x4.splat (get_local 1))))))
+;; NEXT:  }
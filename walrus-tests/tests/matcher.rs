@@ -0,0 +1,80 @@
+extern crate failure;
+extern crate walrus;
+
+use walrus::function::FunctionBuilder;
+use walrus::ir::matcher::{any, find, get_local, i32_const, or, I32AddMatcher, Matcher};
+use walrus::ir::Expr;
+use walrus::ValType;
+
+#[test]
+fn find_add_zero() -> Result<(), failure::Error> {
+    let mut builder = FunctionBuilder::new(&[ValType::I32], Some(ValType::I32));
+    builder
+        .get_local(0)?
+        .i32_const(0)?
+        .i32_add()?
+        .i32_const(0)?
+        .i32_add()?
+        .i32_const(1)?
+        .i32_add()?;
+    let func = builder.finish()?;
+
+    let add_zero = I32AddMatcher::new(any(), i32_const(0));
+    let hits = find(&func, &add_zero);
+    assert_eq!(hits.len(), 2);
+    for hit in hits {
+        match func.expr(hit) {
+            Expr::I32Add(_, rhs) => assert!(i32_const(0).is_match(&func, func.expr(*rhs))),
+            other => panic!("unexpected match: {:?}", other),
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn nested_patterns() -> Result<(), failure::Error> {
+    let mut builder = FunctionBuilder::new(&[ValType::I32, ValType::I32], Some(ValType::I32));
+    builder
+        .get_local(0)?
+        .i32_const(0)?
+        .i32_add()?
+        .get_local(1)?
+        .i32_const(0)?
+        .i32_add()?
+        .i32_add()?;
+    let func = builder.finish()?;
+
+    let local_plus_zero = |n| I32AddMatcher::new(get_local(n), i32_const(0));
+    assert_eq!(find(&func, &local_plus_zero(0)).len(), 1);
+    assert_eq!(find(&func, &local_plus_zero(2)).len(), 0);
+
+    let sum = I32AddMatcher::new(local_plus_zero(0), local_plus_zero(1));
+    let hits = find(&func, &sum);
+    assert_eq!(hits.len(), 1);
+    assert!(sum.is_match(&func, func.expr(hits[0])));
+
+    assert_eq!(find(&func, &or(get_local(0), get_local(1))).len(), 2);
+    Ok(())
+}
+
+#[test]
+fn skips_dead_code() -> Result<(), failure::Error> {
+    let mut builder = FunctionBuilder::new(&[ValType::I32], None);
+    builder
+        .get_local(0)?
+        .i32_const(0)?
+        .i32_add()?
+        .drop()?
+        .return_()?
+        .get_local(0)?
+        .i32_const(0)?
+        .i32_add()?
+        .drop()?;
+    let mut func = builder.finish()?;
+
+    let add_zero = I32AddMatcher::new(any(), i32_const(0));
+    assert_eq!(find(&func, &add_zero).len(), 2);
+    walrus::passes::dce::run(&mut func);
+    assert_eq!(find(&func, &add_zero).len(), 1);
+    Ok(())
+}
@@ -0,0 +1,83 @@
+extern crate failure;
+extern crate parity_wasm;
+extern crate walrus;
+extern crate walrus_tests;
+
+use parity_wasm::elements;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+macro_rules! assert_pass {
+    ($name:ident, $pass:ident, $wasm_path:expr, $wat_path:expr) => {
+        #[test]
+        fn $name() {
+            let module = elements::deserialize_file($wasm_path).unwrap();
+            let type_section = module.type_section().unwrap();
+            let func_section = module.function_section().unwrap();
+            let code_section = module.code_section().unwrap();
+
+            let validation = walrus::validation_context::ValidationContext::for_module(&module)
+                .expect("could not create validation context");
+
+            let checker = walrus_tests::FileCheck::from_file(Path::new($wat_path));
+            let mut output = String::new();
+
+            for (func, body) in func_section
+                .entries()
+                .iter()
+                .zip(code_section.bodies().iter())
+            {
+                let result =
+                    walrus::function::Function::new(&validation, &type_section, func, body);
+                match result {
+                    Err(e) => {
+                        eprintln!("got an error:");
+                        for c in e.iter_chain() {
+                            eprintln!("  {}", c);
+                        }
+                        eprintln!("{}", e.backtrace());
+                        panic!("constructing a new `walrus::Function` failed");
+                    }
+                    Ok(mut func) => {
+                        walrus::passes::$pass::run(&mut func);
+                        output.push_str(&func.to_string());
+                    }
+                }
+            }
+
+            let mut out_file = PathBuf::from($wasm_path);
+            out_file.set_extension("out");
+            fs::write(out_file, &output).expect("should write out file OK");
+
+            checker.check(&output);
+        }
+    };
+}
+
+include!(concat!(env!("OUT_DIR"), "/passes.rs"));
+
+#[test]
+fn dot_skips_merged_blocks() {
+    use walrus::dot::Dot;
+
+    let path = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/passes/simplify-blocks/nested.wasm"
+    );
+    let module = elements::deserialize_file(path).unwrap();
+    let validation = walrus::validation_context::ValidationContext::for_module(&module).unwrap();
+    let func = &module.function_section().unwrap().entries()[0];
+    let body = &module.code_section().unwrap().bodies()[0];
+    let mut func =
+        walrus::function::Function::new(&validation, module.type_section().unwrap(), func, body)
+            .unwrap();
+    walrus::passes::simplify_blocks::run(&mut func);
+
+    let mut dot = vec![];
+    func.dot(&mut dot).unwrap();
+    let dot = String::from_utf8(dot).unwrap();
+    assert!(dot.contains("block_0"));
+    assert!(dot.contains("block_1"));
+    assert!(!dot.contains("block_2"));
+    assert!(!dot.contains("block_3"));
+}
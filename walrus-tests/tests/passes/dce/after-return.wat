@@ -0,0 +1,17 @@
+(module
+  (type (;0;) (func (param i32) (result i32)))
+  (func (;0;) (type 0) (local i32)
+    i32.const 1
+    set_local 1
+    get_local 0
+    return
+    i32.const 2
+    set_local 1
+    get_local 1))
+
+;; CHECK: func {
+;; NEXT:    ;; function entry
+;; NEXT:    block_1():
+;; NEXT:      (set_local 1 (i32.const 1))
+;; NEXT:      (return ((get_local 0)))
+;; NEXT:  }
@@ -0,0 +1,21 @@
+(module
+  (type (;0;) (func (param i32)))
+  (func (;0;) (type 0)
+    block
+      get_local 0
+      set_local 0
+      unreachable
+      get_local 0
+      drop
+    end))
+
+;; CHECK: func {
+;; NEXT:    ;; function entry
+;; NEXT:    block_1():
+;; NEXT:      (br block_3 ())
+;; NEXT:
+;; NEXT:    ;; block
+;; NEXT:    block_3():
+;; NEXT:      (set_local 0 (get_local 0))
+;; NEXT:      (unreachable)
+;; NEXT:  }
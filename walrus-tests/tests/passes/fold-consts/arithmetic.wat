@@ -0,0 +1,35 @@
+(module
+  (type (;0;) (func (param i32) (result i32)))
+  (func (;0;) (type 0) (local i32)
+    i32.const 2
+    i32.const 3
+    i32.add
+    set_local 1
+    i32.const 0
+    i32.const 1
+    i32.sub
+    i32.const 7
+    i32.mul
+    set_local 1
+    i32.const -7
+    i32.const 2
+    i32.rem_s
+    i32.eqz
+    set_local 1
+    i32.const 255
+    i32.popcnt
+    get_local 0
+    i32.add))
+
+;; CHECK: func {
+;; NEXT:    ;; function entry
+;; NEXT:    block_1():
+;; NEXT:      (set_local 1 (i32.const 5))
+;; NEXT:      (set_local 1 (i32.const -7))
+;; NEXT:      (set_local 1 (i32.const 0))
+;; NEXT:      (br block_0 ((i32.add (i32.const 8) (get_local 0))))
+;; NEXT:
+;; NEXT:    ;; function exit
+;; NEXT:    block_0(i32):
+;; NEXT:      (return ((i32.add (i32.const 8) (get_local 0))))
+;; NEXT:  }
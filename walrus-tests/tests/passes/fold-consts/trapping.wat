@@ -0,0 +1,31 @@
+(module
+  (type (;0;) (func (result i32)))
+  (func (;0;) (type 0) (local i32)
+    i32.const 1
+    i32.const 0
+    i32.div_u
+    set_local 0
+    i32.const -2147483648
+    i32.const -1
+    i32.div_s
+    set_local 0
+    i32.const -2147483648
+    i32.const -1
+    i32.rem_s
+    set_local 0
+    i32.const 7
+    i32.const 0
+    i32.rem_s))
+
+;; CHECK: func {
+;; NEXT:    ;; function entry
+;; NEXT:    block_1():
+;; NEXT:      (set_local 0 (i32.div_u (i32.const 1) (i32.const 0)))
+;; NEXT:      (set_local 0 (i32.div_s (i32.const -2147483648) (i32.const -1)))
+;; NEXT:      (set_local 0 (i32.const 0))
+;; NEXT:      (br block_0 ((i32.rem_s (i32.const 7) (i32.const 0))))
+;; NEXT:
+;; NEXT:    ;; function exit
+;; NEXT:    block_0(i32):
+;; NEXT:      (return ((i32.rem_s (i32.const 7) (i32.const 0))))
+;; NEXT:  }
@@ -0,0 +1,20 @@
+(module
+  (type (;0;) (func (result i32)))
+  (global (;0;) i32 (i32.const 42))
+  (func (;0;) (type 0)
+    get_global 0
+    get_global 0
+    i32.add
+    get_global 0
+    i32.add))
+
+;; CHECK: func {
+;; NEXT:    ;; function entry
+;; NEXT:    block_1():
+;; NEXT:      (set_local 0 (get_global 0))
+;; NEXT:      (br block_0 ((i32.add (i32.add (get_local 0) (get_local 0)) (get_local 0))))
+;; NEXT:
+;; NEXT:    ;; function exit
+;; NEXT:    block_0(i32):
+;; NEXT:      (return ((i32.add (i32.add (get_local 0) (get_local 0)) (get_local 0))))
+;; NEXT:  }
@@ -0,0 +1,24 @@
+(module
+  (type (;0;) (func (result i32)))
+  (global (;0;) (mut i32) (i32.const 0))
+  (global (;1;) (mut i32) (i32.const 0))
+  (func (;0;) (type 0)
+    get_global 0
+    get_global 1
+    i32.add
+    set_global 1
+    get_global 0
+    get_global 1
+    i32.add))
+
+;; CHECK: func {
+;; NEXT:    ;; function entry
+;; NEXT:    block_1():
+;; NEXT:      (set_local 0 (get_global 0))
+;; NEXT:      (set_global 1 (i32.add (get_local 0) (get_global 1)))
+;; NEXT:      (br block_0 ((i32.add (get_local 0) (get_global 1))))
+;; NEXT:
+;; NEXT:    ;; function exit
+;; NEXT:    block_0(i32):
+;; NEXT:      (return ((i32.add (get_local 0) (get_global 1))))
+;; NEXT:  }
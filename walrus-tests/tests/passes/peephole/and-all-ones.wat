@@ -0,0 +1,26 @@
+(module
+  (type (;0;) (func (param i32) (result i32)))
+  (func (;0;) (type 0) (local i32)
+    get_local 0
+    i32.const -1
+    i32.and
+    set_local 1
+    i32.const -1
+    get_local 0
+    i32.and
+    set_local 1
+    get_local 0
+    i32.const 1
+    i32.and))
+
+;; CHECK: func {
+;; NEXT:    ;; function entry
+;; NEXT:    block_1():
+;; NEXT:      (set_local 1 (get_local 0))
+;; NEXT:      (set_local 1 (get_local 0))
+;; NEXT:      (br block_0 ((i32.and (get_local 0) (i32.const 1))))
+;; NEXT:
+;; NEXT:    ;; function exit
+;; NEXT:    block_0(i32):
+;; NEXT:      (return ((i32.and (get_local 0) (i32.const 1))))
+;; NEXT:  }
@@ -0,0 +1,34 @@
+(module
+  (type (;0;) (func (param i32) (result i32)))
+  (func (;0;) (type 0) (local i32)
+    get_local 0
+    i32.const 0
+    i32.add
+    set_local 1
+    i32.const 1
+    get_local 0
+    i32.mul
+    i32.const 0
+    i32.sub
+    set_local 1
+    get_local 0
+    i32.const 0
+    i32.mul
+    set_local 1
+    get_local 0
+    i32.eqz
+    i32.eqz
+    i32.eqz))
+
+;; CHECK: func {
+;; NEXT:    ;; function entry
+;; NEXT:    block_1():
+;; NEXT:      (set_local 1 (get_local 0))
+;; NEXT:      (set_local 1 (get_local 0))
+;; NEXT:      (set_local 1 (i32.const 0))
+;; NEXT:      (br block_0 ((i32.eqz (get_local 0))))
+;; NEXT:
+;; NEXT:    ;; function exit
+;; NEXT:    block_0(i32):
+;; NEXT:      (return ((i32.eqz (get_local 0))))
+;; NEXT:  }
@@ -0,0 +1,23 @@
+(module
+  (type (;0;) (func (param i32) (result i32)))
+  (memory 1)
+  (func (;0;) (type 0)
+    get_local 0
+    i32.load
+    i32.const 0
+    i32.mul
+    get_local 0
+    i32.load
+    i32.const 0
+    i32.add
+    i32.add))
+
+;; CHECK: func {
+;; NEXT:    ;; function entry
+;; NEXT:    block_1():
+;; NEXT:      (br block_0 ((i32.add (i32.mul (i32.load (get_local 0)) (i32.const 0)) (i32.load (get_local 0)))))
+;; NEXT:
+;; NEXT:    ;; function exit
+;; NEXT:    block_0(i32):
+;; NEXT:      (return ((i32.add (i32.mul (i32.load (get_local 0)) (i32.const 0)) (i32.load (get_local 0)))))
+;; NEXT:  }
@@ -0,0 +1,30 @@
+(module
+  (type (;0;) (func (param i32)))
+  (func (;0;) (type 0)
+    block (result i32)
+      i32.const 1
+      get_local 0
+      br_if 0
+      drop
+      i32.const 2
+    end
+    drop))
+
+;; CHECK: func {
+;; NEXT:    ;; function entry
+;; NEXT:    block_1():
+;; NEXT:      (br block_3 ())
+;; NEXT:
+;; NEXT:    ;; block
+;; NEXT:    block_3(i32):
+;; NEXT:      (drop (br_if (get_local 0) block_2 ((i32.const 1))))
+;; NEXT:      (br block_2 ((i32.const 2)))
+;; NEXT:
+;; NEXT:    ;; block continuation
+;; NEXT:    block_2(i32):
+;; NEXT:      (br block_0 ())
+;; NEXT:
+;; NEXT:    ;; function exit
+;; NEXT:    block_0():
+;; NEXT:      (return ())
+;; NEXT:  }
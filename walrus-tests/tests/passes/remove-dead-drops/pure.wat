@@ -0,0 +1,28 @@
+(module
+  (type (;0;) (func (param i32) (result i32)))
+  (func (;0;) (type 0)
+    i32.const 1
+    drop
+    get_local 0
+    drop
+    get_local 0
+    i32.const 2
+    i32.add
+    i32.eqz
+    drop
+    i32.const 3
+    i32.const 4
+    get_local 0
+    select
+    drop
+    get_local 0))
+
+;; CHECK: func {
+;; NEXT:    ;; function entry
+;; NEXT:    block_1():
+;; NEXT:      (br block_0 ((get_local 0)))
+;; NEXT:
+;; NEXT:    ;; function exit
+;; NEXT:    block_0(i32):
+;; NEXT:      (return ((get_local 0)))
+;; NEXT:  }
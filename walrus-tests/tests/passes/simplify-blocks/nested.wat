@@ -0,0 +1,24 @@
+(module
+  (type (;0;) (func (param i32) (result i32)))
+  (func (;0;) (type 0)
+    block
+      block
+        i32.const 1
+        set_local 0
+      end
+      i32.const 2
+      set_local 0
+    end
+    get_local 0))
+
+;; CHECK: func {
+;; NEXT:    ;; function entry
+;; NEXT:    block_1():
+;; NEXT:      (set_local 0 (i32.const 1))
+;; NEXT:      (set_local 0 (i32.const 2))
+;; NEXT:      (br block_0 ((get_local 0)))
+;; NEXT:
+;; NEXT:    ;; function exit
+;; NEXT:    block_0(i32):
+;; NEXT:      (return ((get_local 0)))
+;; NEXT:  }
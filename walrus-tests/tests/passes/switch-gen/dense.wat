@@ -0,0 +1,66 @@
+(module
+  (type (;0;) (func (param i32) (result i32)))
+  (func (;0;) (type 0)
+    block
+      block
+        block
+          block
+            (br_if 0 (i32.eq (get_local 0) (i32.const 1)))
+            (br_if 1 (i32.eq (get_local 0) (i32.const 2)))
+            (br_if 2 (i32.eq (i32.const 4) (get_local 0)))
+            (br_if 3 (i32.eq (get_local 0) (i32.const 3)))
+            (return (i32.const 0))
+          end
+          (return (i32.const 10))
+        end
+        (return (i32.const 20))
+      end
+      (return (i32.const 40))
+    end
+    i32.const 30))
+
+;; CHECK: func {
+;; NEXT:    ;; function entry
+;; NEXT:    block_1():
+;; NEXT:      (br block_3 ())
+;; NEXT:
+;; NEXT:    ;; block
+;; NEXT:    block_3():
+;; NEXT:      (br block_5 ())
+;; NEXT:
+;; NEXT:    ;; block
+;; NEXT:    block_5():
+;; NEXT:      (br block_7 ())
+;; NEXT:
+;; NEXT:    ;; block
+;; NEXT:    block_7():
+;; NEXT:      (br block_9 ())
+;; NEXT:
+;; NEXT:    ;; block
+;; NEXT:    block_9():
+;; NEXT:      (br_table (i32.sub (get_local 0) (i32.const 1)) [block_8 block_6 block_2 block_4] block_10 ())
+;; NEXT:
+;; NEXT:    ;; br_if chain continuation
+;; NEXT:    block_10():
+;; NEXT:      (return ((i32.const 0)))
+;; NEXT:
+;; NEXT:    ;; block continuation
+;; NEXT:    block_8():
+;; NEXT:      (return ((i32.const 10)))
+;; NEXT:
+;; NEXT:    ;; block continuation
+;; NEXT:    block_6():
+;; NEXT:      (return ((i32.const 20)))
+;; NEXT:
+;; NEXT:    ;; block continuation
+;; NEXT:    block_2():
+;; NEXT:      (br block_0 ((i32.const 30)))
+;; NEXT:
+;; NEXT:    ;; function exit
+;; NEXT:    block_0(i32):
+;; NEXT:      (return ((i32.const 30)))
+;; NEXT:
+;; NEXT:    ;; block continuation
+;; NEXT:    block_4():
+;; NEXT:      (return ((i32.const 40)))
+;; NEXT:  }
@@ -0,0 +1,8 @@
+(module
+  (type (;0;) (func (param i32)))
+  (func (;0;) (type 0) (local i32)
+    get_local 0
+    if
+      i32.const 1
+      set_local 1
+    end))
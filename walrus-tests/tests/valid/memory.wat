@@ -0,0 +1,14 @@
+(module
+  (type (;0;) (func (param i32) (result i32)))
+  (func (;0;) (type 0)
+    get_local 0
+    get_local 0
+    i32.load offset=4
+    i32.store8 offset=1 align=1
+    get_local 0
+    get_local 0
+    i64.load16_s align=1
+    i64.store32 align=2
+    get_local 0
+    i32.load8_u)
+  (memory (;0;) 1))
@@ -0,0 +1,9 @@
+(module
+  (type (;0;) (func (param i32)))
+  (func (;0;) (type 0)
+    block
+      block
+      end
+      i32.const 2
+      set_local 0
+    end))
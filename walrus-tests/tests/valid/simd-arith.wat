@@ -0,0 +1,20 @@
+(module
+  (type (;0;) (func (param i32 f32) (result f32)))
+  (func (;0;) (type 0) (local v128)
+    get_local 0
+    i32x4.splat
+    get_local 0
+    i32x4.splat
+    i32x4.mul
+    i32x4.neg
+    get_local 0
+    i8x16.splat
+    i8x16.add_saturate_u
+    set_local 2
+    get_local 1
+    f32x4.splat
+    f32x4.sqrt
+    get_local 1
+    f32x4.splat
+    f32x4.min
+    f32x4.extract_lane 0))
@@ -0,0 +1,8 @@
+(module
+  (type (;0;) (func (param i32) (result i32)))
+  (func (;0;) (type 0)
+    get_local 0
+    i32x4.splat
+    i32.const 7
+    i32x4.replace_lane 1
+    i32x4.extract_lane 2))
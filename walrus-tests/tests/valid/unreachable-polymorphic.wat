@@ -0,0 +1,17 @@
+(module
+  (type (;0;) (func (param i32) (result i32)))
+  (func (;0;) (type 0)
+    unreachable
+    i32.add
+    i32.eqz
+    drop
+    block (result i32)
+      get_local 0
+      br 0
+      f32x4.neg
+      drop
+      i32.mul
+    end
+    return
+    i32x4.splat
+    i32x4.extract_lane 0))
@@ -0,0 +1,141 @@
+extern crate walrus;
+
+use walrus::error::ErrorKind;
+use walrus::ir::Value;
+use walrus::ValType;
+
+/// Get a value's type and bits, so that NaNs can be compared.
+fn bits(value: Value) -> (ValType, u128) {
+    let bits = match value {
+        Value::I32(n) => u128::from(n as u32),
+        Value::I64(n) => u128::from(n as u64),
+        Value::F32(x) => u128::from(x.to_bits()),
+        Value::F64(x) => u128::from(x.to_bits()),
+        Value::V128(n) => n,
+    };
+    (value.ty(), bits)
+}
+
+fn parse(s: &str, ty: ValType) -> Value {
+    Value::parse(s, ty).unwrap_or_else(|e| panic!("could not parse `{}`: {}", s, e))
+}
+
+/// Assert that the value is displayed as the given text, and parses back to
+/// the same value.
+fn round_trip(value: Value, text: &str) {
+    assert_eq!(value.to_string(), text);
+    assert_eq!(bits(parse(text, value.ty())), bits(value));
+}
+
+fn invalid(s: &str, ty: ValType) {
+    let err = Value::parse(s, ty).unwrap_err();
+    assert!(
+        err.iter_chain()
+            .any(|c| c.downcast_ref::<ErrorKind>() == Some(&ErrorKind::InvalidValue)),
+        "`{}` should not parse as {}",
+        s,
+        ty
+    );
+}
+
+#[test]
+fn integers() {
+    round_trip(Value::I32(0), "0");
+    round_trip(Value::I32(-42), "-42");
+    round_trip(Value::I32(i32::MIN), "-2147483648");
+    round_trip(Value::I64(i64::MAX), "9223372036854775807");
+
+    assert_eq!(parse("0xffff_ffff", ValType::I32), Value::I32(-1));
+    assert_eq!(parse("4294967295", ValType::I32), Value::I32(-1));
+    assert_eq!(parse("-0x10", ValType::I64), Value::I64(-16));
+    assert_eq!(parse("+1_000", ValType::I64), Value::I64(1000));
+
+    invalid("4294967296", ValType::I32);
+    invalid("-2147483649", ValType::I32);
+    invalid("1__0", ValType::I32);
+    invalid("0x", ValType::I64);
+    invalid("", ValType::I64);
+}
+
+#[test]
+fn floats() {
+    round_trip(Value::F32(1.0), "0x1p+0");
+    round_trip(Value::F32(-0.0), "-0x0p+0");
+    round_trip(Value::F32(0.1), "0x1.99999ap-4");
+    round_trip(Value::F64(0.1), "0x1.999999999999ap-4");
+    round_trip(Value::F64(-1024.5), "-0x1.002p+10");
+    round_trip(Value::F32(f32::INFINITY), "inf");
+    round_trip(Value::F64(f64::NEG_INFINITY), "-inf");
+    round_trip(Value::F32(f32::NAN), "nan");
+    round_trip(Value::F32(f32::from_bits(1)), "0x0.000002p-126");
+    round_trip(Value::F64(f64::from_bits(1)), "0x0.0000000000001p-1022");
+
+    assert_eq!(parse("1.5e3", ValType::F32), Value::F32(1500.0));
+    assert_eq!(parse("-0.25", ValType::F64), Value::F64(-0.25));
+    assert_eq!(parse("0x1.8p1", ValType::F64), Value::F64(3.0));
+    assert_eq!(parse("0x10", ValType::F32), Value::F32(16.0));
+    assert_eq!(parse("1_000.5", ValType::F64), Value::F64(1000.5));
+
+    // Hex floats that cannot be represented exactly are rounded to nearest,
+    // ties to even.
+    assert_eq!(parse("0x1.000001p+0", ValType::F32), Value::F32(1.0));
+    assert_eq!(
+        parse("0x1.000003p+0", ValType::F32),
+        Value::F32(f32::from_bits(0x3f80_0002))
+    );
+    assert_eq!(
+        parse("0x1.fffffe7p+127", ValType::F32),
+        Value::F32(f32::MAX)
+    );
+
+    invalid("0x1p+128", ValType::F32);
+    invalid("0x1.ffffffp+127", ValType::F32);
+    invalid("1e400", ValType::F64);
+    invalid("nan:0x0", ValType::F32);
+    invalid("nan:0x800000", ValType::F32);
+    invalid("infinity", ValType::F64);
+    invalid("1.5x", ValType::F64);
+}
+
+#[test]
+fn signaling_nan() {
+    let snan = f32::from_bits(0x7fa0_0000);
+    round_trip(Value::F32(snan), "nan:0x200000");
+    round_trip(Value::F32(-snan), "-nan:0x200000");
+    round_trip(Value::F64(f64::from_bits(0x7ff0_0000_0000_0001)), "nan:0x1");
+}
+
+#[test]
+fn v128() {
+    let n = 0x0f0e_0d0c_0b0a_0908_0706_0504_0302_0100;
+    round_trip(
+        Value::V128(n),
+        "i8x16 0x00 0x01 0x02 0x03 0x04 0x05 0x06 0x07 \
+         0x08 0x09 0x0a 0x0b 0x0c 0x0d 0x0e 0x0f",
+    );
+
+    assert_eq!(
+        parse(
+            "i32x4 0x03020100 0x07060504 0x0b0a0908 0x0f0e0d0c",
+            ValType::V128
+        ),
+        Value::V128(n)
+    );
+    assert_eq!(
+        parse("i16x8 -1 0 0 0 0 0 0 0xffff", ValType::V128),
+        Value::V128(0xffff_0000_0000_0000_0000_0000_0000_ffff)
+    );
+    assert_eq!(
+        parse("f32x4 1 0 0 -0x0p+0", ValType::V128),
+        Value::V128(0x8000_0000_0000_0000_0000_0000_3f80_0000)
+    );
+    assert_eq!(
+        parse("f64x2 nan:0x1 0", ValType::V128),
+        Value::V128(0x7ff0_0000_0000_0001)
+    );
+
+    invalid("i32x4 1 2 3", ValType::V128);
+    invalid("i32x4 1 2 3 4 5", ValType::V128);
+    invalid("i8x16 256 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0", ValType::V128);
+    invalid("v128 0", ValType::V128);
+}